@@ -2,8 +2,9 @@ use anyhow::Result;
 use linux_embedded_hal::spidev::{SpiModeFlags, SpidevOptions};
 use linux_embedded_hal::sysfs_gpio::Direction;
 use linux_embedded_hal::{Delay, Spidev, SysfsPin};
+use rfm69::lowpowerlab::Filter;
 use rfm69::{low_power_lab_defaults, Rfm69};
-use utilities::{rfm_error, Packet};
+use utilities::rfm_error;
 
 fn main() -> Result<()> {
     // Configure CS pin
@@ -33,16 +34,15 @@ fn main() -> Result<()> {
     }
 
     // Prepare buffer to store the received data
-    let mut buffer = [0; 64];
-    rfm_error!(rfm.recv(&mut buffer))?;
-    let packet = Packet::from_bytes(&buffer);
-    println!("{:?}", packet);
+    let mut buffer = [0; 65];
+    let frame = rfm_error!(rfm.recv_lpl(Filter::Address(1), &mut buffer))?;
+    println!("{:?}", frame);
 
     // Send the ACK if it was requested
-    if packet.ack_requested() {
-        let ack = Packet::new(packet.to(), packet.from(), Vec::new(), true, false);
+    if frame.ack_requested() {
+        let ack = frame.ack(1);
         println!("{:?}", ack);
-        rfm_error!(rfm.send(&ack.as_bytes()))?;
+        rfm_error!(rfm.send_lpl(&ack))?;
     }
 
     // Un-export the CS pin
@@ -2,8 +2,9 @@ use anyhow::Result;
 use linux_embedded_hal::spidev::{SpiModeFlags, SpidevOptions};
 use linux_embedded_hal::sysfs_gpio::Direction;
 use linux_embedded_hal::{Delay, Spidev, SysfsPin};
+use rfm69::lowpowerlab::{Filter, Frame, ACK_REQUESTED};
 use rfm69::{low_power_lab_defaults, Rfm69};
-use utilities::{rfm_error, Packet};
+use utilities::rfm_error;
 
 fn main() -> Result<()> {
     // Configure CS pin
@@ -33,14 +34,18 @@ fn main() -> Result<()> {
     }
 
     // Prepare struct for the data
-    let packet = Packet::new(10, 1, Vec::from(b"Hello, world!".as_ref()), false, true);
-    println!("{:?}", packet);
-    rfm_error!(rfm.send(&packet.as_bytes()))?;
+    let frame = Frame {
+        to: 1,
+        from: 10,
+        control: ACK_REQUESTED,
+        payload: b"Hello, world!",
+    };
+    println!("{:?}", frame);
+    rfm_error!(rfm.send_lpl(&frame))?;
 
     // Wait for ACK to arrive
-    let mut buffer = [0; 64];
-    rfm_error!(rfm.recv(&mut buffer))?;
-    let ack = Packet::from_bytes(&buffer);
+    let mut buffer = [0; 65];
+    let ack = rfm_error!(rfm.recv_lpl(Filter::Address(10), &mut buffer))?;
     println!("{:?}", ack);
     if ack.ack_received() {
         println!("ACK received");
@@ -0,0 +1,54 @@
+use crate::registers::Registers;
+
+const SIZE: usize = Registers::TestDagc as usize + 1;
+
+/// Shadow copy of the register file. Registers that are changed by the chip itself, such as the
+/// mode, FIFO, IRQ flags, RSSI, AFC/FEI and temperature values are never cached. The mode is
+/// changed by the chip in listen mode and with automatic modes.
+pub(crate) struct Cache {
+    values: [u8; SIZE],
+    valid: u128,
+}
+
+impl Cache {
+    pub(crate) fn new() -> Self {
+        Cache {
+            values: [0; SIZE],
+            valid: 0,
+        }
+    }
+
+    pub(crate) fn get(&self, reg: Registers) -> Option<u8> {
+        let addr = reg as usize;
+        if self.valid & (1 << addr) != 0 {
+            Some(self.values[addr])
+        } else {
+            None
+        }
+    }
+
+    /// Stores the values of a burst access starting at `reg`.
+    pub(crate) fn store(&mut self, reg: Registers, data: &[u8]) {
+        let start = reg as usize;
+        if start == Registers::Fifo as usize {
+            return;
+        }
+        for (addr, &value) in (start..SIZE).zip(data) {
+            if !is_volatile(addr) {
+                self.values[addr] = value;
+                self.valid |= 1 << addr;
+            }
+        }
+    }
+
+    pub(crate) fn invalidate(&mut self) {
+        self.valid = 0;
+    }
+}
+
+fn is_volatile(addr: usize) -> bool {
+    matches!(
+        addr,
+        0x00 | 0x01 | 0x0A | 0x0C | 0x1E..=0x24 | 0x27 | 0x28 | 0x4E | 0x4F
+    )
+}
@@ -20,6 +20,43 @@ impl OutputPin for NoCs {
     }
 }
 
+/// Reset line of the chip. This is implemented for [`NoReset`] and for every [`OutputPin`].
+pub trait ResetPin {
+    type Error;
+
+    /// Drives the reset line, `true` holds the chip in reset. Returns `false` when no reset line
+    /// is connected.
+    fn set_reset(&mut self, active: bool) -> core::result::Result<bool, Self::Error>;
+}
+
+impl<P> ResetPin for P
+where
+    P: OutputPin,
+{
+    type Error = P::Error;
+
+    fn set_reset(&mut self, active: bool) -> core::result::Result<bool, Self::Error> {
+        if active {
+            self.set_high()?;
+        } else {
+            self.set_low()?;
+        }
+        Ok(true)
+    }
+}
+
+/// An implementation of [`ResetPin`] which does nothing. This is used when the reset line of the
+/// chip is not connected.
+pub struct NoReset;
+
+impl ResetPin for NoReset {
+    type Error = core::convert::Infallible;
+
+    fn set_reset(&mut self, _: bool) -> core::result::Result<bool, Self::Error> {
+        Ok(false)
+    }
+}
+
 pub(crate) struct CsGuard<'a, T, Ecs, Espi>
 where
     T: OutputPin<Error = Ecs>,
@@ -1,26 +1,28 @@
 use embedded_hal::blocking::delay::DelayMs;
 use embedded_hal::digital::v2::OutputPin;
 
+use crate::cs::ResetPin;
 use crate::error::Result;
 use crate::registers::{
-    ContinuousDagc, DataMode, DccCutoff, FifoMode, InterPacketRxDelay, LnaConfig, LnaGain,
+    ClkOut, ContinuousDagc, DataMode, DccCutoff, FifoMode, InterPacketRxDelay, LnaConfig, LnaGain,
     LnaImpedance, Mode, Modulation, ModulationShaping, ModulationType, PacketConfig, PacketDc,
-    PacketFiltering, PacketFormat, RxBw, RxBwFsk,
+    PacketFiltering, PacketFormat, Registers, RxBw, RxBwFsk,
 };
 use crate::rw::ReadWrite;
 use crate::Rfm69;
 
 /// Configures RFM69 according to [LowPowerLab](https://github.com/LowPowerLab/RFM69) Arduino
 /// library
-pub fn low_power_lab_defaults<T, S, D, Ecs, Espi>(
-    mut rfm: Rfm69<T, S, D>,
+pub fn low_power_lab_defaults<T, S, D, R, Ecs, Espi>(
+    mut rfm: Rfm69<T, S, D, R>,
     network_id: u8,
     frequency: f32,
-) -> Result<Rfm69<T, S, D>, Ecs, Espi>
+) -> Result<Rfm69<T, S, D, R>, Ecs, Espi>
 where
     T: OutputPin<Error = Ecs>,
     S: ReadWrite<Error = Espi>,
     D: DelayMs<u8>,
+    R: ResetPin,
 {
     rfm.mode(Mode::Standby)?;
     rfm.modulation(Modulation {
@@ -54,3 +56,266 @@ where
     rfm.continuous_dagc(ContinuousDagc::ImprovedMarginAfcLowBetaOn0)?;
     Ok(rfm)
 }
+
+/// Configures RFM69 with the values that the datasheet recommends instead of the reset values of
+/// `RegLna (0x18), RegRxBw (0x19), RegAfcBw (0x1A), RegDioMapping2 (0x26), RegRssiThresh (0x29),
+/// RegSyncValue1-8 (0x2F-0x36), RegFifoThresh (0x3C), RegTestDagc (0x6F)`. `RegAfcFei (0x1E)` is
+/// not written, its recommended value only differs from the reset value in the read-only
+/// `AfcDone` bit.
+pub fn recommended_defaults<T, S, D, R, Ecs, Espi>(
+    mut rfm: Rfm69<T, S, D, R>,
+) -> Result<Rfm69<T, S, D, R>, Ecs, Espi>
+where
+    T: OutputPin<Error = Ecs>,
+    S: ReadWrite<Error = Espi>,
+    D: DelayMs<u8>,
+    R: ResetPin,
+{
+    rfm.lna(LnaConfig {
+        zin: LnaImpedance::Ohm200,
+        gain_select: LnaGain::AgcLoop,
+    })?;
+    rfm.rx_bw(RxBw {
+        dcc_cutoff: DccCutoff::Percent4,
+        rx_bw: RxBwFsk::Khz10dot4,
+    })?;
+    rfm.rx_afc_bw(RxBw {
+        dcc_cutoff: DccCutoff::Percent1,
+        rx_bw: RxBwFsk::Khz50dot0,
+    })?;
+    rfm.clk_out(ClkOut::Off)?;
+    rfm.rssi_threshold(0xe4)?;
+    rfm.write_many(Registers::SyncValue1, &[0x01; 8])?;
+    rfm.write(Registers::FifoThresh, FifoMode::NotEmpty.value())?;
+    rfm.continuous_dagc(ContinuousDagc::ImprovedMarginAfcLowBetaOn0)?;
+    Ok(rfm)
+}
+
+/// Modem configurations of the `RH_RF69` driver of the Arduino
+/// [RadioHead](https://www.airspayce.com/mikem/arduino/RadioHead/) library. FSK and GFSK
+/// configurations are named by bitrate and frequency deviation in kbps and kHz, OOK
+/// configurations by bitrate and receiver bandwidth.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ModemConfigChoice {
+    FskRb2Fd5,
+    FskRb2dot4Fd4dot8,
+    FskRb4dot8Fd9dot6,
+    FskRb9dot6Fd19dot2,
+    FskRb19dot2Fd38dot4,
+    FskRb38dot4Fd76dot8,
+    FskRb57dot6Fd120,
+    FskRb125Fd125,
+    FskRb250Fd250,
+    FskRb55555Fd50,
+    GfskRb2Fd5,
+    GfskRb2dot4Fd4dot8,
+    GfskRb4dot8Fd9dot6,
+    GfskRb9dot6Fd19dot2,
+    GfskRb19dot2Fd38dot4,
+    GfskRb38dot4Fd76dot8,
+    GfskRb57dot6Fd120,
+    GfskRb125Fd125,
+    GfskRb250Fd250,
+    GfskRb55555Fd50,
+    OokRb1Bw1,
+    OokRb1dot2Bw75,
+    OokRb2dot4Bw4dot8,
+    OokRb4dot8Bw9dot6,
+    OokRb9dot6Bw19dot2,
+    OokRb19dot2Bw38dot4,
+    OokRb32Bw64,
+}
+
+const CONFIG_FSK: u8 = 0x00;
+const CONFIG_GFSK: u8 = 0x01;
+const CONFIG_OOK: u8 = 0x08;
+const CONFIG_WHITE: u8 = 0xd0;
+
+impl ModemConfigChoice {
+    /// Values of `RegDataModul (0x02), RegBitrateMsb-Lsb (0x03-0x04), RegFdevMsb-Lsb (0x05-0x06),
+    /// RegRxBw (0x19), RegAfcBw (0x1A), RegPacketConfig1 (0x37)`, as in the RadioHead table.
+    fn value(self) -> [u8; 8] {
+        match self {
+            ModemConfigChoice::FskRb2Fd5 => {
+                [CONFIG_FSK, 0x3e, 0x80, 0x00, 0x52, 0xf4, 0xf4, CONFIG_WHITE]
+            }
+            ModemConfigChoice::FskRb2dot4Fd4dot8 => {
+                [CONFIG_FSK, 0x34, 0x15, 0x00, 0x4f, 0xf4, 0xf4, CONFIG_WHITE]
+            }
+            ModemConfigChoice::FskRb4dot8Fd9dot6 => {
+                [CONFIG_FSK, 0x1a, 0x0b, 0x00, 0x9d, 0xf4, 0xf4, CONFIG_WHITE]
+            }
+            ModemConfigChoice::FskRb9dot6Fd19dot2 => {
+                [CONFIG_FSK, 0x0d, 0x05, 0x01, 0x3b, 0xf4, 0xf4, CONFIG_WHITE]
+            }
+            ModemConfigChoice::FskRb19dot2Fd38dot4 => {
+                [CONFIG_FSK, 0x06, 0x83, 0x02, 0x75, 0xf3, 0xf3, CONFIG_WHITE]
+            }
+            ModemConfigChoice::FskRb38dot4Fd76dot8 => {
+                [CONFIG_FSK, 0x03, 0x41, 0x04, 0xea, 0xf2, 0xf2, CONFIG_WHITE]
+            }
+            ModemConfigChoice::FskRb57dot6Fd120 => {
+                [CONFIG_FSK, 0x02, 0x2c, 0x07, 0xae, 0xe2, 0xe2, CONFIG_WHITE]
+            }
+            ModemConfigChoice::FskRb125Fd125 => {
+                [CONFIG_FSK, 0x01, 0x00, 0x08, 0x00, 0xe1, 0xe1, CONFIG_WHITE]
+            }
+            ModemConfigChoice::FskRb250Fd250 => {
+                [CONFIG_FSK, 0x00, 0x80, 0x10, 0x00, 0xe0, 0xe0, CONFIG_WHITE]
+            }
+            ModemConfigChoice::FskRb55555Fd50 => {
+                [CONFIG_FSK, 0x02, 0x40, 0x03, 0x33, 0x42, 0x42, CONFIG_WHITE]
+            }
+            ModemConfigChoice::GfskRb2Fd5 => [
+                CONFIG_GFSK,
+                0x3e,
+                0x80,
+                0x00,
+                0x52,
+                0xf4,
+                0xf5,
+                CONFIG_WHITE,
+            ],
+            ModemConfigChoice::GfskRb2dot4Fd4dot8 => [
+                CONFIG_GFSK,
+                0x34,
+                0x15,
+                0x00,
+                0x4f,
+                0xf4,
+                0xf4,
+                CONFIG_WHITE,
+            ],
+            ModemConfigChoice::GfskRb4dot8Fd9dot6 => [
+                CONFIG_GFSK,
+                0x1a,
+                0x0b,
+                0x00,
+                0x9d,
+                0xf4,
+                0xf4,
+                CONFIG_WHITE,
+            ],
+            ModemConfigChoice::GfskRb9dot6Fd19dot2 => [
+                CONFIG_GFSK,
+                0x0d,
+                0x05,
+                0x01,
+                0x3b,
+                0xf4,
+                0xf4,
+                CONFIG_WHITE,
+            ],
+            ModemConfigChoice::GfskRb19dot2Fd38dot4 => [
+                CONFIG_GFSK,
+                0x06,
+                0x83,
+                0x02,
+                0x75,
+                0xf3,
+                0xf3,
+                CONFIG_WHITE,
+            ],
+            ModemConfigChoice::GfskRb38dot4Fd76dot8 => [
+                CONFIG_GFSK,
+                0x03,
+                0x41,
+                0x04,
+                0xea,
+                0xf2,
+                0xf2,
+                CONFIG_WHITE,
+            ],
+            ModemConfigChoice::GfskRb57dot6Fd120 => [
+                CONFIG_GFSK,
+                0x02,
+                0x2c,
+                0x07,
+                0xae,
+                0xe2,
+                0xe2,
+                CONFIG_WHITE,
+            ],
+            ModemConfigChoice::GfskRb125Fd125 => [
+                CONFIG_GFSK,
+                0x01,
+                0x00,
+                0x08,
+                0x00,
+                0xe1,
+                0xe1,
+                CONFIG_WHITE,
+            ],
+            ModemConfigChoice::GfskRb250Fd250 => [
+                CONFIG_GFSK,
+                0x00,
+                0x80,
+                0x10,
+                0x00,
+                0xe0,
+                0xe0,
+                CONFIG_WHITE,
+            ],
+            ModemConfigChoice::GfskRb55555Fd50 => [
+                CONFIG_GFSK,
+                0x02,
+                0x40,
+                0x03,
+                0x33,
+                0x42,
+                0x42,
+                CONFIG_WHITE,
+            ],
+            ModemConfigChoice::OokRb1Bw1 => {
+                [CONFIG_OOK, 0x7d, 0x00, 0x00, 0x10, 0x88, 0x88, CONFIG_WHITE]
+            }
+            ModemConfigChoice::OokRb1dot2Bw75 => {
+                [CONFIG_OOK, 0x68, 0x2b, 0x00, 0x10, 0xf1, 0xf1, CONFIG_WHITE]
+            }
+            ModemConfigChoice::OokRb2dot4Bw4dot8 => {
+                [CONFIG_OOK, 0x34, 0x15, 0x00, 0x10, 0xf5, 0xf5, CONFIG_WHITE]
+            }
+            ModemConfigChoice::OokRb4dot8Bw9dot6 => {
+                [CONFIG_OOK, 0x1a, 0x0b, 0x00, 0x10, 0xf4, 0xf4, CONFIG_WHITE]
+            }
+            ModemConfigChoice::OokRb9dot6Bw19dot2 => {
+                [CONFIG_OOK, 0x0d, 0x05, 0x00, 0x10, 0xf3, 0xf3, CONFIG_WHITE]
+            }
+            ModemConfigChoice::OokRb19dot2Bw38dot4 => {
+                [CONFIG_OOK, 0x06, 0x83, 0x00, 0x10, 0xf2, 0xf2, CONFIG_WHITE]
+            }
+            ModemConfigChoice::OokRb32Bw64 => {
+                [CONFIG_OOK, 0x03, 0xe8, 0x00, 0x10, 0xe2, 0xe2, CONFIG_WHITE]
+            }
+        }
+    }
+}
+
+/// Configures RFM69 like the `RH_RF69` driver of the Arduino
+/// [RadioHead](https://www.airspayce.com/mikem/arduino/RadioHead/) library, with the given modem
+/// configuration. Modulation, bitrate, frequency deviation, bandwidths and whitening are written
+/// exactly as RadioHead does, the output power has to be set separately.
+pub fn radio_head_defaults<T, S, D, R, Ecs, Espi>(
+    mut rfm: Rfm69<T, S, D, R>,
+    modem_config: ModemConfigChoice,
+    frequency: f32,
+) -> Result<Rfm69<T, S, D, R>, Ecs, Espi>
+where
+    T: OutputPin<Error = Ecs>,
+    S: ReadWrite<Error = Espi>,
+    D: DelayMs<u8>,
+    R: ResetPin,
+{
+    rfm.mode(Mode::Standby)?;
+    rfm.write(Registers::FifoThresh, FifoMode::NotEmpty.value())?;
+    rfm.continuous_dagc(ContinuousDagc::ImprovedMarginAfcLowBetaOn0)?;
+    let config = modem_config.value();
+    rfm.write_many(Registers::DataModul, &config[0..5])?;
+    rfm.write_many(Registers::RxBw, &config[5..7])?;
+    rfm.write(Registers::PacketConfig1, config[7])?;
+    rfm.sync(&[0x2d, 0xd4])?;
+    rfm.preamble(4)?;
+    rfm.frequency(frequency)?;
+    rfm.aes(&[])?;
+    Ok(rfm)
+}
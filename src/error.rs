@@ -12,8 +12,51 @@ pub enum Error<Ecs, Espi> {
     AesKeySize,
     /// Sync sequence is too long
     SyncSize,
+    /// Sync word bit error tolerance is larger than 7
+    SyncTolerance,
     /// Packet size is longer than receive buffer
     BufferTooSmall,
     /// Packet exceeds maximum size (255 for send_large)
     PacketTooLarge,
+    /// Output power is out of range for the module variant
+    PowerOutOfRange,
+    /// Over current protection trim is out of range or not in 5 mA steps
+    OcpOutOfRange,
+    /// Over current protection limit is too low for the output power
+    OcpLimit,
+    /// Listen mode duration is too long
+    ListenDuration,
+    /// No packet was received before the receive timeout expired
+    RxTimeout,
+    /// Channel stayed busy for all CSMA retries
+    ChannelBusy,
+    /// Reset pin error
+    Reset,
+    /// Chip version is not 0x24, the chip is missing or not wired correctly
+    UnknownChip(u8),
+    /// Frame cannot be encoded or decoded
+    Frame(FrameError),
+    /// No acknowledgement was received for all retries
+    NoAck,
+}
+
+/// Errors of the packet framing codecs.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FrameError {
+    /// Buffer is shorter than the frame
+    Truncated,
+    /// Length byte is shorter than the header
+    InvalidLength,
+    /// Payload exceeds maximum size of the frame format
+    PayloadTooLarge,
+    /// Frame is longer than the output buffer
+    BufferTooSmall,
+    /// Address exceeds maximum of the frame format
+    InvalidAddress,
+}
+
+impl<Ecs, Espi> From<FrameError> for Error<Ecs, Espi> {
+    fn from(error: FrameError) -> Self {
+        Error::Frame(error)
+    }
 }
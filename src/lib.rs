@@ -27,19 +27,25 @@
 
 #![cfg_attr(not(test), no_std)]
 
-pub use crate::cs::NoCs;
-pub use crate::defaults::low_power_lab_defaults;
-pub use crate::error::Error;
-pub use crate::rfm::Rfm69;
+pub use crate::cs::{NoCs, NoReset, ResetPin};
+pub use crate::defaults::{
+    low_power_lab_defaults, radio_head_defaults, recommended_defaults, ModemConfigChoice,
+};
+pub use crate::error::{Error, FrameError};
+pub use crate::rfm::{CsmaConfig, NoiseFloor, RadioConfig, ReceivedPacket, Rfm69};
 pub use crate::rw::{ReadWrite, SpiTransactional};
 
+mod cache;
 mod cs;
 mod defaults;
 mod error;
 mod rfm;
 mod rw;
 
+pub mod lowpowerlab;
 pub mod registers;
+pub mod rh_reliable_datagram;
+pub mod rh_rf69;
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,167 @@
+//! Packet format of the Arduino [LowPowerLab](https://github.com/LowPowerLab/RFM69) library.
+//! LowPowerLab packets are variable length packets with a 3 byte header `to, from, control`
+//! after the length byte. Node addresses are 10 bits long, the upper 2 bits of the target address
+//! are stored in bits 3-2 and of the sender address in bits 1-0 of the control byte. Addresses
+//! are filtered in software, because the hardware address filtering is limited to 8 bits. The
+//! radio has to be configured with [`low_power_lab_defaults`](crate::low_power_lab_defaults) to
+//! use the same modem configuration, sync words and preamble.
+
+use embedded_hal::blocking::delay::DelayMs;
+use embedded_hal::digital::v2::OutputPin;
+
+use crate::cs::ResetPin;
+use crate::error::{Error, FrameError, Result};
+use crate::rw::ReadWrite;
+use crate::Rfm69;
+
+/// Length of the header after the length byte.
+pub const HEADER_LEN: usize = 3;
+/// Maximal payload length.
+pub const MAX_PAYLOAD_LEN: usize = 61;
+/// Maximal node address.
+pub const MAX_ADDRESS: u16 = 0x3ff;
+/// Destination address which is accepted by all nodes.
+pub const BROADCAST_ADDRESS: u16 = 0;
+/// Control flag of acknowledgement frames.
+pub const ACK_SENT: u8 = 0x80;
+/// Control flag of frames that request an acknowledgement.
+pub const ACK_REQUESTED: u8 = 0x40;
+/// Control flag of frames that request the RSSI of the frame in the acknowledgement, used by the
+/// automatic transmission control of the LowPowerLab library. The flag is decoded only,
+/// acknowledgements carrying the RSSI are not supported.
+pub const RSSI_REQUESTED: u8 = 0x20;
+
+/// Frame with a payload borrowed from the receive or transmit buffer. The `control` byte holds
+/// the flags only, the upper address bits are packed by the codec.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Frame<'a> {
+    pub to: u16,
+    pub from: u16,
+    pub control: u8,
+    pub payload: &'a [u8],
+}
+
+impl<'a> Frame<'a> {
+    /// Decodes a frame from a buffer starting with the length byte.
+    pub fn decode(buffer: &'a [u8]) -> core::result::Result<Self, FrameError> {
+        let len = usize::from(*buffer.first().ok_or(FrameError::Truncated)?);
+        if len < HEADER_LEN {
+            return Err(FrameError::InvalidLength);
+        } else if len > HEADER_LEN + MAX_PAYLOAD_LEN {
+            return Err(FrameError::PayloadTooLarge);
+        } else if buffer.len() <= len {
+            return Err(FrameError::Truncated);
+        }
+        let control = buffer[3];
+        Ok(Frame {
+            to: u16::from(buffer[1]) | u16::from(control & 0x0c) << 6,
+            from: u16::from(buffer[2]) | u16::from(control & 0x03) << 8,
+            control: control & 0xf0,
+            payload: &buffer[1 + HEADER_LEN..=len],
+        })
+    }
+
+    /// Encodes the frame including the length byte and returns the number of bytes written.
+    pub fn encode(&self, buffer: &mut [u8]) -> core::result::Result<usize, FrameError> {
+        let len = HEADER_LEN + self.payload.len();
+        if self.payload.len() > MAX_PAYLOAD_LEN {
+            return Err(FrameError::PayloadTooLarge);
+        } else if self.to > MAX_ADDRESS || self.from > MAX_ADDRESS {
+            return Err(FrameError::InvalidAddress);
+        } else if buffer.len() <= len {
+            return Err(FrameError::BufferTooSmall);
+        }
+        let control = (self.control & 0xf0)
+            | ((self.to & 0x300) >> 6) as u8
+            | ((self.from & 0x300) >> 8) as u8;
+        buffer[..=HEADER_LEN].copy_from_slice(&[
+            len as u8,
+            self.to as u8,
+            self.from as u8,
+            control,
+        ]);
+        buffer[1 + HEADER_LEN..=len].copy_from_slice(self.payload);
+        Ok(len + 1)
+    }
+
+    pub fn ack_requested(&self) -> bool {
+        self.control & ACK_REQUESTED != 0
+    }
+
+    pub fn ack_received(&self) -> bool {
+        self.control & ACK_SENT != 0
+    }
+
+    pub fn rssi_requested(&self) -> bool {
+        self.control & RSSI_REQUESTED != 0
+    }
+
+    /// Acknowledgement frame for this frame sent from `address`, with an empty payload. The
+    /// address of the receiving node is needed because the frame may have been broadcasted.
+    pub fn ack(&self, address: u16) -> Frame<'static> {
+        Frame {
+            to: self.from,
+            from: address,
+            control: ACK_SENT,
+            payload: &[],
+        }
+    }
+}
+
+/// Address filtering of received frames.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Filter {
+    /// Accepts frames sent to the address or to [`BROADCAST_ADDRESS`]
+    Address(u16),
+    /// Accepts all frames
+    Promiscuous,
+}
+
+impl Filter {
+    pub fn accepts(&self, frame: &Frame) -> bool {
+        match self {
+            Filter::Address(address) => frame.to == *address || frame.to == BROADCAST_ADDRESS,
+            Filter::Promiscuous => true,
+        }
+    }
+}
+
+impl<T, S, D, R, Ecs, Espi> Rfm69<T, S, D, R>
+where
+    T: OutputPin<Error = Ecs>,
+    S: ReadWrite<Error = Espi>,
+    D: DelayMs<u8>,
+    R: ResetPin,
+{
+    /// Sends a LowPowerLab frame with [`send`](Self::send).
+    /// Returns `Frame` if the payload is longer than [`MAX_PAYLOAD_LEN`] or an address is larger
+    /// than [`MAX_ADDRESS`].
+    pub fn send_lpl(&mut self, frame: &Frame) -> Result<(), Ecs, Espi> {
+        let mut buffer = [0u8; 1 + HEADER_LEN + MAX_PAYLOAD_LEN];
+        let len = frame.encode(&mut buffer)?;
+        self.send(&buffer[..len])
+    }
+
+    /// Receives LowPowerLab frames with [`recv`](Self::recv) until a frame passes the filter.
+    /// Malformed frames are dropped. The buffer should be able to hold the longest frame of
+    /// `1 + HEADER_LEN + MAX_PAYLOAD_LEN` bytes.
+    /// Returns `BufferTooSmall` if the buffer cannot hold the length byte and the header.
+    pub fn recv_lpl<'b>(
+        &mut self,
+        filter: Filter,
+        buffer: &'b mut [u8],
+    ) -> Result<Frame<'b>, Ecs, Espi> {
+        if buffer.len() <= HEADER_LEN {
+            return Err(Error::BufferTooSmall);
+        }
+        loop {
+            self.recv(buffer)?;
+            if let Ok(frame) = Frame::decode(buffer) {
+                if filter.accepts(&frame) {
+                    break;
+                }
+            }
+        }
+        Ok(Frame::decode(buffer)?)
+    }
+}
@@ -105,8 +105,12 @@ pub enum Mode {
     Standby = 0x04,
     Transmitter = 0x0C,
     Receiver = 0x10,
+    /// Listen mode, the chip periodically switches between idle and receive mode as configured by
+    /// [`ListenConfig`]. DIO mappings for receive mode are applied while listening.
+    Listen = 0x44,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Modulation {
     pub data_mode: DataMode,
     pub modulation_type: ModulationType,
@@ -115,24 +119,44 @@ pub struct Modulation {
 
 impl Modulation {
     pub(crate) fn value(&self) -> u8 {
-        (*self).data_mode as u8 | (*self).modulation_type as u8 | (*self).shaping as u8
+        self.data_mode as u8 | self.modulation_type as u8 | self.shaping as u8
+    }
+
+    pub(crate) fn from_value(value: u8) -> Self {
+        Modulation {
+            data_mode: match value & 0x60 {
+                0x40 => DataMode::Continuous,
+                0x60 => DataMode::ContinuousBitSync,
+                _ => DataMode::Packet,
+            },
+            modulation_type: match value & 0x18 {
+                0x08 => ModulationType::Ook,
+                _ => ModulationType::Fsk,
+            },
+            shaping: match value & 0x03 {
+                0x00 => ModulationShaping::Shaping00,
+                0x01 => ModulationShaping::Shaping01,
+                0x02 => ModulationShaping::Shaping10,
+                _ => ModulationShaping::Shaping11,
+            },
+        }
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum DataMode {
     Packet = 0x00,
     Continuous = 0x40,
     ContinuousBitSync = 0x60,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum ModulationType {
     Fsk = 0x00,
     Ook = 0x08,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum ModulationShaping {
     Shaping00 = 0x00,
     Shaping01 = 0x01,
@@ -147,6 +171,17 @@ pub struct DioMapping {
     pub dio_mode: DioMode,
 }
 
+impl DioMapping {
+    /// Maps the `LowBat` event to DIO2, which provides it in every mode.
+    pub fn low_bat() -> Self {
+        DioMapping {
+            pin: DioPin::Dio2,
+            dio_type: DioType::Dio10,
+            dio_mode: DioMode::Always,
+        }
+    }
+}
+
 #[derive(Copy, Clone)]
 pub enum DioPin {
     Dio0 = 14,
@@ -165,24 +200,187 @@ pub enum DioType {
     Dio11 = 0b11,
 }
 
+/// Clock output on DIO5 in `RegDioMapping2 (0x26)`, the crystal oscillator frequency divided by
+/// the given ratio or the RC oscillator.
+#[derive(Copy, Clone)]
+pub enum ClkOut {
+    Fxosc = 0b000,
+    Div2 = 0b001,
+    Div4 = 0b010,
+    Div8 = 0b011,
+    Div16 = 0b100,
+    Div32 = 0b101,
+    Rc = 0b110,
+    Off = 0b111,
+}
+
 #[derive(PartialEq, Copy, Clone)]
 pub enum DioMode {
     Rx,
     Tx,
     Both,
+    /// Mapping is applied in every mode, including sleep and standby
+    Always,
 }
 
 impl DioMode {
     #[inline]
     pub(crate) fn eq(self, mode: Mode) -> bool {
         match self {
-            DioMode::Both => mode == Mode::Transmitter || mode == Mode::Receiver,
-            DioMode::Rx => mode == Mode::Receiver,
+            DioMode::Both => {
+                mode == Mode::Transmitter || mode == Mode::Receiver || mode == Mode::Listen
+            }
+            DioMode::Rx => mode == Mode::Receiver || mode == Mode::Listen,
             DioMode::Tx => mode == Mode::Transmitter,
+            DioMode::Always => true,
         }
     }
 }
 
+/// Listen mode configuration, durations are in microseconds. The coefficients and resolutions
+/// of `RegListen1-3 (0x0D-0x0F)` are computed from the durations, choosing the finest resolution
+/// that can represent the duration. The longest duration is 255 * 262 ms.
+pub struct ListenConfig {
+    pub idle_duration: u32,
+    pub rx_duration: u32,
+    pub criteria: ListenCriteria,
+    pub end: ListenEnd,
+}
+
+impl ListenConfig {
+    pub(crate) fn value(&self) -> Option<[u8; 3]> {
+        let (idle_resolution, idle_coef) = listen_coefficient(self.idle_duration)?;
+        let (rx_resolution, rx_coef) = listen_coefficient(self.rx_duration)?;
+        let reg =
+            (idle_resolution << 6) | (rx_resolution << 4) | self.criteria as u8 | self.end as u8;
+        Some([reg, idle_coef, rx_coef])
+    }
+}
+
+fn listen_coefficient(duration: u32) -> Option<(u8, u8)> {
+    [(0b01, 64), (0b10, 4_100), (0b11, 262_000)]
+        .iter()
+        .map(|&(resolution, step)| (resolution, duration.saturating_add(step / 2) / step))
+        .find(|&(_, coef)| coef <= 255)
+        .map(|(resolution, coef)| (resolution, coef.max(1) as u8))
+}
+
+#[derive(Copy, Clone)]
+pub enum ListenCriteria {
+    /// Packet reception is accepted when the signal strength is above `RssiThreshold`
+    RssiThreshold = 0x00,
+    /// Packet reception is accepted when the signal strength is above `RssiThreshold` and
+    /// `SyncAddress` is detected
+    RssiThresholdSyncAddress = 0x08,
+}
+
+#[derive(Copy, Clone)]
+pub enum ListenEnd {
+    /// The chip stays in receive mode when a packet is detected, listen mode stops and must be
+    /// disabled
+    StayRx = 0x00,
+    /// The chip stays in receive mode until `PayloadReady` or `Timeout` and then goes to the mode
+    /// defined in `RegOpMode`, listen mode stops and must be disabled
+    ModeAfterRx = 0x02,
+    /// The chip stays in receive mode until `PayloadReady` or `Timeout` and then resumes listen
+    /// mode
+    ResumeListen = 0x04,
+}
+
+/// Automatic modes in `RegAutoModes (0x3B)`. The chip switches from the mode set in
+/// `RegOpMode (0x01)` to the intermediate mode when the enter condition occurs and returns back
+/// when the exit condition occurs.
+#[derive(Copy, Clone)]
+pub struct AutoModes {
+    pub enter: EnterCondition,
+    pub exit: ExitCondition,
+    pub intermediate: IntermediateMode,
+}
+
+impl AutoModes {
+    pub(crate) fn value(&self) -> u8 {
+        self.enter as u8 | self.exit as u8 | self.intermediate as u8
+    }
+}
+
+#[derive(Copy, Clone)]
+pub enum EnterCondition {
+    None = 0x00,
+    FifoNotEmpty = 0x20,
+    FifoLevel = 0x40,
+    CrcOk = 0x60,
+    PayloadReady = 0x80,
+    SyncAddress = 0xA0,
+    PacketSent = 0xC0,
+    FifoEmpty = 0xE0,
+}
+
+#[derive(Copy, Clone)]
+pub enum ExitCondition {
+    None = 0x00,
+    FifoEmpty = 0x04,
+    FifoLevelOrTimeout = 0x08,
+    CrcOkOrTimeout = 0x0C,
+    PayloadReadyOrTimeout = 0x10,
+    SyncAddressOrTimeout = 0x14,
+    PacketSent = 0x18,
+    Timeout = 0x1C,
+}
+
+#[derive(Copy, Clone)]
+pub enum IntermediateMode {
+    Sleep = 0b00,
+    Standby = 0b01,
+    Receiver = 0b10,
+    Transmitter = 0b11,
+}
+
+/// Sync word configuration in `RegSyncConfig (0x2E), RegSyncValue1-8 (0x2F-0x36)`. Only the
+/// first `len` bytes of `value` are used, zero length disables the sync word. `tolerance` is
+/// the number of bit errors tolerated in the sync word, 0 to 7.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SyncConfig {
+    pub fill_condition: FifoFillCondition,
+    pub tolerance: u8,
+    pub len: u8,
+    pub value: [u8; 8],
+}
+
+impl SyncConfig {
+    pub(crate) fn value(&self) -> u8 {
+        let reg = self.fill_condition as u8 | (self.tolerance & 0x07);
+        if self.len == 0 {
+            return reg;
+        }
+        0x80 | reg | ((self.len - 1) & 0x07) << 3
+    }
+
+    pub(crate) fn from_value(reg: u8, value: [u8; 8]) -> Self {
+        SyncConfig {
+            fill_condition: match reg & 0x40 {
+                0x40 => FifoFillCondition::Always,
+                _ => FifoFillCondition::SyncAddress,
+            },
+            tolerance: reg & 0x07,
+            len: if reg & 0x80 != 0 {
+                ((reg >> 3) & 0x07) + 1
+            } else {
+                0
+            },
+            value,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FifoFillCondition {
+    /// FIFO is filled when `SyncAddress` interrupt occurs
+    SyncAddress = 0x00,
+    /// FIFO is filled as long as the condition is set
+    Always = 0x40,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct PacketConfig {
     pub format: PacketFormat,
     pub dc: PacketDc,
@@ -192,6 +390,46 @@ pub struct PacketConfig {
     pub auto_rx_restart: bool,
 }
 
+impl PacketConfig {
+    /// Values of `RegPacketConfig1 (0x37), RegPayloadLength (0x38)` and the packet bits of
+    /// `RegPacketConfig2 (0x3D)`.
+    pub(crate) fn value(&self) -> [u8; 3] {
+        let (format, len) = match self.format {
+            PacketFormat::Fixed(len) => (0x00, len),
+            PacketFormat::Variable(len) => (0x80, len),
+        };
+        [
+            format | self.dc as u8 | (self.crc as u8) << 4 | self.filtering as u8,
+            len,
+            self.interpacket_rx_delay as u8 | (self.auto_rx_restart as u8) << 1,
+        ]
+    }
+
+    pub(crate) fn from_value(config1: u8, payload_length: u8, config2: u8) -> Self {
+        PacketConfig {
+            format: if config1 & 0x80 != 0 {
+                PacketFormat::Variable(payload_length)
+            } else {
+                PacketFormat::Fixed(payload_length)
+            },
+            dc: match config1 & 0x60 {
+                0x20 => PacketDc::Manchester,
+                0x40 => PacketDc::Whitening,
+                _ => PacketDc::None,
+            },
+            crc: config1 & 0x10 != 0,
+            filtering: match config1 & 0x06 {
+                0x02 => PacketFiltering::Address,
+                0x04 => PacketFiltering::Broadcast,
+                _ => PacketFiltering::None,
+            },
+            interpacket_rx_delay: InterPacketRxDelay::from_value(config2),
+            auto_rx_restart: config2 & 0x02 != 0,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum InterPacketRxDelay {
     Delay1Bit = 0x00,
     Delay2Bits = 0x10,
@@ -207,38 +445,98 @@ pub enum InterPacketRxDelay {
     Delay2048Bits = 0xB0,
 }
 
+impl InterPacketRxDelay {
+    pub(crate) fn from_value(value: u8) -> Self {
+        match value & 0xf0 {
+            0x00 => InterPacketRxDelay::Delay1Bit,
+            0x10 => InterPacketRxDelay::Delay2Bits,
+            0x20 => InterPacketRxDelay::Delay4Bits,
+            0x30 => InterPacketRxDelay::Delay8Bits,
+            0x40 => InterPacketRxDelay::Delay16Bits,
+            0x50 => InterPacketRxDelay::Delay32Bits,
+            0x60 => InterPacketRxDelay::Delay64Bits,
+            0x70 => InterPacketRxDelay::Delay128Bits,
+            0x80 => InterPacketRxDelay::Delay256Bits,
+            0x90 => InterPacketRxDelay::Delay512Bits,
+            0xA0 => InterPacketRxDelay::Delay1024Bits,
+            _ => InterPacketRxDelay::Delay2048Bits,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum PacketFiltering {
     None = 0x00,
     Address = 0x02,
     Broadcast = 0x04,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum PacketDc {
     None = 0x00,
     Manchester = 0x20,
     Whitening = 0x40,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum PacketFormat {
     Variable(u8),
     Fixed(u8),
 }
 
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum FifoMode {
     NotEmpty,
     Level(u8),
 }
 
+impl FifoMode {
+    /// Value of `RegFifoThresh (0x3C)`, the threshold is set to its default 0x0F for `NotEmpty`.
+    pub(crate) fn value(&self) -> u8 {
+        match self {
+            FifoMode::NotEmpty => 0x8f,
+            FifoMode::Level(level) => level & 0x7f,
+        }
+    }
+
+    pub(crate) fn from_value(value: u8) -> Self {
+        if value & 0x80 != 0 {
+            FifoMode::NotEmpty
+        } else {
+            FifoMode::Level(value & 0x7f)
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct LnaConfig {
     pub zin: LnaImpedance,
     pub gain_select: LnaGain,
 }
 
+impl LnaConfig {
+    pub(crate) fn value(&self) -> u8 {
+        self.zin as u8 | self.gain_select as u8
+    }
+
+    pub(crate) fn from_value(value: u8) -> Self {
+        LnaConfig {
+            zin: match value & 0x80 {
+                0x80 => LnaImpedance::Ohm200,
+                _ => LnaImpedance::Ohm50,
+            },
+            gain_select: LnaGain::from_value(value),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum LnaImpedance {
     Ohm50 = 0x00,
     Ohm200 = 0x80,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum LnaGain {
     AgcLoop = 0b000,
     G1 = 0b001,
@@ -249,6 +547,66 @@ pub enum LnaGain {
     G6 = 0b110,
 }
 
+pub struct LowBatConfig {
+    pub enabled: bool,
+    pub trim: LowBatTrim,
+}
+
+impl LowBatConfig {
+    pub(crate) fn value(&self) -> u8 {
+        (self.enabled as u8) << 3 | self.trim as u8
+    }
+}
+
+#[derive(Copy, Clone)]
+pub enum LowBatTrim {
+    V1dot695 = 0b000,
+    V1dot764 = 0b001,
+    V1dot835 = 0b010,
+    V1dot905 = 0b011,
+    V1dot976 = 0b100,
+    V2dot045 = 0b101,
+    V2dot116 = 0b110,
+    V2dot185 = 0b111,
+}
+
+impl LnaGain {
+    pub(crate) fn from_value(value: u8) -> Self {
+        match value & 0x07 {
+            0b001 => LnaGain::G1,
+            0b010 => LnaGain::G2,
+            0b011 => LnaGain::G3,
+            0b100 => LnaGain::G4,
+            0b101 => LnaGain::G5,
+            0b110 => LnaGain::G6,
+            _ => LnaGain::AgcLoop,
+        }
+    }
+}
+
+/// AGC reference level and thresholds in `RegAgcRef (0x14), RegAgcThresh1-3 (0x15-0x17)`.
+pub struct AgcConfig {
+    /// Reference level is computed from the receiver bandwidth when enabled
+    pub auto_ref: bool,
+    /// Floor reference level in dB below -80 dBm, 0 to 63
+    pub ref_level: u8,
+    /// SNR margin in dB, 0 to 7
+    pub snr_margin: u8,
+    /// Steps between the LNA gains in dB, the first step is 0 to 31, the others 0 to 15
+    pub steps: [u8; 5],
+}
+
+impl AgcConfig {
+    pub(crate) fn value(&self) -> [u8; 4] {
+        [
+            (self.auto_ref as u8) << 6 | (self.ref_level & 0x3f),
+            (self.snr_margin & 0x07) << 5 | (self.steps[0] & 0x1f),
+            (self.steps[1] & 0x0f) << 4 | (self.steps[2] & 0x0f),
+            (self.steps[3] & 0x0f) << 4 | (self.steps[4] & 0x0f),
+        ]
+    }
+}
+
 pub enum SensitivityBoost {
     Normal = 0x1B,
     HighSensitivity = 0x2D,
@@ -264,12 +622,191 @@ pub enum Pa13dBm2 {
     High20dBm = 0x7C,
 }
 
+/// Power amplifier selection and output power in `RegPaLevel (0x11)`. `output_power` is in
+/// 1 dB steps from 0 to 31, the output power in dBm depends on the selected amplifiers.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PaLevel {
+    pub pa0: bool,
+    pub pa1: bool,
+    pub pa2: bool,
+    pub output_power: u8,
+}
+
+impl PaLevel {
+    pub(crate) fn value(&self) -> u8 {
+        (self.pa0 as u8) << 7
+            | (self.pa1 as u8) << 6
+            | (self.pa2 as u8) << 5
+            | (self.output_power & 0x1f)
+    }
+
+    pub(crate) fn from_value(value: u8) -> Self {
+        PaLevel {
+            pa0: value & 0x80 != 0,
+            pa1: value & 0x40 != 0,
+            pa2: value & 0x20 != 0,
+            output_power: value & 0x1f,
+        }
+    }
+}
+
+/// Power amplifier layout of the module, RFM69W and RFM69CW are low power variants with PA0 on
+/// the RFIO pin, RFM69HW and RFM69HCW are high power variants with PA1 and PA2 on the PA_BOOST pin.
+#[derive(Copy, Clone, PartialEq)]
+pub enum ModuleVariant {
+    LowPower,
+    HighPower,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PaRamp {
+    Ms3dot4 = 0b0000,
+    Ms2 = 0b0001,
+    Ms1 = 0b0010,
+    Us500 = 0b0011,
+    Us250 = 0b0100,
+    Us125 = 0b0101,
+    Us100 = 0b0110,
+    Us62 = 0b0111,
+    Us50 = 0b1000,
+    Us40 = 0b1001,
+    Us31 = 0b1010,
+    Us25 = 0b1011,
+    Us20 = 0b1100,
+    Us15 = 0b1101,
+    Us12 = 0b1110,
+    Us10 = 0b1111,
+}
+
+impl PaRamp {
+    pub(crate) fn from_value(value: u8) -> Self {
+        match value & 0x0f {
+            0b0000 => PaRamp::Ms3dot4,
+            0b0001 => PaRamp::Ms2,
+            0b0010 => PaRamp::Ms1,
+            0b0011 => PaRamp::Us500,
+            0b0100 => PaRamp::Us250,
+            0b0101 => PaRamp::Us125,
+            0b0110 => PaRamp::Us100,
+            0b0111 => PaRamp::Us62,
+            0b1000 => PaRamp::Us50,
+            0b1001 => PaRamp::Us40,
+            0b1010 => PaRamp::Us31,
+            0b1011 => PaRamp::Us25,
+            0b1100 => PaRamp::Us20,
+            0b1101 => PaRamp::Us15,
+            0b1110 => PaRamp::Us12,
+            _ => PaRamp::Us10,
+        }
+    }
+}
+
+/// Over current protection, `trim` is the current limit in mA between 45 and 120 mA in 5 mA
+/// steps.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct OcpConfig {
+    pub enabled: bool,
+    pub trim: u8,
+}
+
+impl OcpConfig {
+    pub(crate) fn value(&self) -> Option<u8> {
+        if self.trim < 45 || self.trim > 120 || self.trim % 5 != 0 {
+            return None;
+        }
+        Some(((self.enabled as u8) << 4) | ((self.trim - 45) / 5))
+    }
+
+    pub(crate) fn from_value(value: u8) -> Self {
+        OcpConfig {
+            enabled: value & 0x10 != 0,
+            trim: 45 + 5 * (value & 0x0f),
+        }
+    }
+}
+
 pub enum ContinuousDagc {
     Normal = 0x00,
     ImprovedMarginAfcLowBetaOn1 = 0x20,
     ImprovedMarginAfcLowBetaOn0 = 0x30,
 }
 
+/// OOK demodulator threshold in `RegOokPeak (0x1B), RegOokAvg (0x1C), RegOokFix (0x1D)`.
+/// Threshold values are in dB.
+pub enum OokThreshold {
+    Fixed(u8),
+    Peak {
+        step: OokPeakStep,
+        decrement: OokPeakDecrement,
+        floor: u8,
+    },
+    Average(OokAverageFilter),
+}
+
+impl OokThreshold {
+    pub(crate) fn value(&self) -> [u8; 3] {
+        match self {
+            OokThreshold::Fixed(threshold) => [0x00, 0x80, *threshold],
+            OokThreshold::Peak {
+                step,
+                decrement,
+                floor,
+            } => [0x40 | *step as u8 | *decrement as u8, 0x80, *floor],
+            OokThreshold::Average(filter) => [0x80, *filter as u8, 0x06],
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+pub enum OokPeakStep {
+    Db0dot5 = 0x00,
+    Db1dot0 = 0x08,
+    Db1dot5 = 0x10,
+    Db2dot0 = 0x18,
+    Db3dot0 = 0x20,
+    Db4dot0 = 0x28,
+    Db5dot0 = 0x30,
+    Db6dot0 = 0x38,
+}
+
+#[derive(Copy, Clone)]
+pub enum OokPeakDecrement {
+    OncePerChip = 0b000,
+    OnceEvery2Chips = 0b001,
+    OnceEvery4Chips = 0b010,
+    OnceEvery8Chips = 0b011,
+    TwicePerChip = 0b100,
+    FourTimesPerChip = 0b101,
+    EightTimesPerChip = 0b110,
+    SixteenTimesPerChip = 0b111,
+}
+
+/// Filter coefficient of the average threshold, as a fraction of the chip rate.
+#[derive(Copy, Clone)]
+pub enum OokAverageFilter {
+    ChipRateBy32Pi = 0x00,
+    ChipRateBy8Pi = 0x40,
+    ChipRateBy4Pi = 0x80,
+    ChipRateBy2Pi = 0xC0,
+}
+
+pub struct AfcConfig {
+    pub auto_on: bool,
+    pub auto_clear: bool,
+}
+
+impl AfcConfig {
+    pub(crate) fn value(&self) -> u8 {
+        (self.auto_clear as u8) << 3 | (self.auto_on as u8) << 2
+    }
+}
+
+pub enum AfcMode {
+    Standard = 0x00,
+    ImprovedLowBeta = 0x20,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct RxBw<T>
 where
     T: RxBwFreq,
@@ -278,6 +815,47 @@ where
     pub rx_bw: T,
 }
 
+impl<T> RxBw<T>
+where
+    T: RxBwFreq,
+{
+    pub(crate) fn value(&self) -> u8 {
+        self.dcc_cutoff as u8 | self.rx_bw.value()
+    }
+}
+
+/// Receiver bandwidth of either modulation type, the same register value means half the
+/// bandwidth in OOK.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RxBwConfig {
+    Fsk(RxBw<RxBwFsk>),
+    Ook(RxBw<RxBwOok>),
+}
+
+impl RxBwConfig {
+    pub(crate) fn value(&self) -> u8 {
+        match self {
+            RxBwConfig::Fsk(rx_bw) => rx_bw.value(),
+            RxBwConfig::Ook(rx_bw) => rx_bw.value(),
+        }
+    }
+
+    pub(crate) fn from_value(value: u8, modulation_type: ModulationType) -> Self {
+        let dcc_cutoff = DccCutoff::from_value(value);
+        match modulation_type {
+            ModulationType::Fsk => RxBwConfig::Fsk(RxBw {
+                dcc_cutoff,
+                rx_bw: RxBwFsk::from_value(value),
+            }),
+            ModulationType::Ook => RxBwConfig::Ook(RxBw {
+                dcc_cutoff,
+                rx_bw: RxBwOok::from_value(value),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum DccCutoff {
     Percent16 = 0x00,
     Percent8 = 0x20,
@@ -289,10 +867,26 @@ pub enum DccCutoff {
     Percent0dot125 = 0xE0,
 }
 
+impl DccCutoff {
+    pub(crate) fn from_value(value: u8) -> Self {
+        match value & 0xe0 {
+            0x00 => DccCutoff::Percent16,
+            0x20 => DccCutoff::Percent8,
+            0x40 => DccCutoff::Percent4,
+            0x60 => DccCutoff::Percent2,
+            0x80 => DccCutoff::Percent1,
+            0xA0 => DccCutoff::Percent0dot5,
+            0xC0 => DccCutoff::Percent0dot25,
+            _ => DccCutoff::Percent0dot125,
+        }
+    }
+}
+
 pub trait RxBwFreq {
     fn value(&self) -> u8;
 }
 
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum RxBwFsk {
     Khz2dot6,
     Khz3dot1,
@@ -352,6 +946,38 @@ impl RxBwFreq for RxBwFsk {
     }
 }
 
+impl RxBwFsk {
+    pub(crate) fn from_value(value: u8) -> Self {
+        match value & 0x1f {
+            0x17 => RxBwFsk::Khz2dot6,
+            0x0f => RxBwFsk::Khz3dot1,
+            0x07 => RxBwFsk::Khz3dot9,
+            0x16 => RxBwFsk::Khz5dot2,
+            0x0e => RxBwFsk::Khz6dot3,
+            0x06 => RxBwFsk::Khz7dot8,
+            0x15 => RxBwFsk::Khz10dot4,
+            0x0d => RxBwFsk::Khz12dot5,
+            0x05 => RxBwFsk::Khz15dot6,
+            0x14 => RxBwFsk::Khz20dot8,
+            0x0c => RxBwFsk::Khz25dot0,
+            0x04 => RxBwFsk::Khz31dot3,
+            0x13 => RxBwFsk::Khz41dot7,
+            0x0b => RxBwFsk::Khz50dot0,
+            0x03 => RxBwFsk::Khz62dot5,
+            0x12 => RxBwFsk::Khz83dot3,
+            0x0a => RxBwFsk::Khz100dot0,
+            0x02 => RxBwFsk::Khz125dot0,
+            0x11 => RxBwFsk::Khz166dot7,
+            0x09 => RxBwFsk::Khz200dot0,
+            0x01 => RxBwFsk::Khz250dot0,
+            0x10 => RxBwFsk::Khz333dot3,
+            0x08 => RxBwFsk::Khz400dot0,
+            _ => RxBwFsk::Khz500dot0,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum RxBwOok {
     Khz1dot3,
     Khz1dot6,
@@ -410,3 +1036,34 @@ impl RxBwFreq for RxBwOok {
         }
     }
 }
+
+impl RxBwOok {
+    pub(crate) fn from_value(value: u8) -> Self {
+        match value & 0x1f {
+            0x17 => RxBwOok::Khz1dot3,
+            0x0f => RxBwOok::Khz1dot6,
+            0x07 => RxBwOok::Khz2dot0,
+            0x16 => RxBwOok::Khz2dot6,
+            0x0e => RxBwOok::Khz3dot1,
+            0x06 => RxBwOok::Khz3dot9,
+            0x15 => RxBwOok::Khz5dot2,
+            0x0d => RxBwOok::Khz6dot3,
+            0x05 => RxBwOok::Khz7dot8,
+            0x14 => RxBwOok::Khz10dot4,
+            0x0c => RxBwOok::Khz12dot5,
+            0x04 => RxBwOok::Khz15dot6,
+            0x13 => RxBwOok::Khz20dot8,
+            0x0b => RxBwOok::Khz25dot0,
+            0x03 => RxBwOok::Khz31dot3,
+            0x12 => RxBwOok::Khz41dot7,
+            0x0a => RxBwOok::Khz50dot0,
+            0x02 => RxBwOok::Khz62dot5,
+            0x11 => RxBwOok::Khz83dot3,
+            0x09 => RxBwOok::Khz100dot0,
+            0x01 => RxBwOok::Khz125dot0,
+            0x10 => RxBwOok::Khz166dot7,
+            0x08 => RxBwOok::Khz200dot0,
+            _ => RxBwOok::Khz250dot0,
+        }
+    }
+}
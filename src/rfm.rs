@@ -4,11 +4,15 @@ use embedded_hal::blocking::delay::DelayMs;
 use embedded_hal::blocking::spi::Transactional;
 use embedded_hal::digital::v2::OutputPin;
 
-use crate::cs::{CsGuard, NoCs};
+use crate::cache::Cache;
+use crate::cs::{CsGuard, NoCs, NoReset, ResetPin};
 use crate::error::{Error, Result};
 use crate::registers::{
-    ContinuousDagc, DioMapping, DioPin, FifoMode, LnaConfig, Mode, Modulation, Pa13dBm1, Pa13dBm2,
-    PacketConfig, PacketFormat, Registers, RxBw, RxBwFreq, SensitivityBoost,
+    AfcConfig, AfcMode, AgcConfig, AutoModes, ClkOut, ContinuousDagc, DioMapping, DioPin,
+    EnterCondition, ExitCondition, FifoFillCondition, FifoMode, IntermediateMode, ListenConfig,
+    LnaConfig, LnaGain, LowBatConfig, Mode, Modulation, ModuleVariant, OcpConfig, OokThreshold,
+    Pa13dBm1, Pa13dBm2, PaLevel, PaRamp, PacketConfig, Registers, RxBw, RxBwConfig, RxBwFreq,
+    SensitivityBoost, SyncConfig,
 };
 use crate::rw::{ReadWrite, SpiTransactional};
 
@@ -16,13 +20,131 @@ const FOSC: f32 = 32_000_000.0;
 const FSTEP: f32 = FOSC / 524_288.0; // FOSC/2^19
 
 /// Main struct to interact with RFM69 chip.
-pub struct Rfm69<T, S, D> {
+pub struct Rfm69<T, S, D, R = NoReset> {
     pub(crate) spi: S,
     cs: T,
     delay: D,
+    pub(crate) reset: R,
     mode: Mode,
     dio: [Option<DioMapping>; 6],
+    clk_out: ClkOut,
     rssi: f32,
+    variant: ModuleVariant,
+    pa1_pa2: bool,
+    high_power: bool,
+    ocp: OcpConfig,
+    seed: u32,
+    cache: Option<Cache>,
+}
+
+/// Packet metadata returned by [`Rfm69::recv_packet`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ReceivedPacket {
+    /// Length of the payload stored in the buffer
+    pub len: usize,
+    /// Address byte of the packet, available when address filtering is enabled
+    pub address: Option<u8>,
+    /// RSSI in dBm
+    pub rssi: f32,
+    /// Frequency error in Hz
+    pub fei: f32,
+    /// AFC correction in Hz
+    pub afc: f32,
+}
+
+/// RSSI statistics in dBm returned by [`Rfm69::noise_floor`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NoiseFloor {
+    /// Mean of the samples
+    pub mean: f32,
+    /// Lowest sample
+    pub min: f32,
+    /// Highest sample
+    pub max: f32,
+}
+
+/// Listen-before-talk policy used by [`Rfm69::send_csma`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CsmaConfig {
+    /// The channel is busy when the RSSI in dBm is above the threshold
+    pub threshold: f32,
+    /// Number of channel assessments after the first one
+    pub retries: u8,
+    /// Initial backoff window in milliseconds, doubled after every busy assessment
+    pub backoff: u16,
+    /// Maximal backoff window in milliseconds
+    pub max_backoff: u16,
+}
+
+/// Radio configuration decoded from the registers by [`Rfm69::config`] and written by
+/// [`Rfm69::apply`]. The bitrate is in bps, the frequency deviation and the frequency are in Hz.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RadioConfig {
+    pub modulation: Modulation,
+    pub bit_rate: f32,
+    pub fdev: f32,
+    pub frequency: f32,
+    pub pa_level: PaLevel,
+    pub pa_ramp: PaRamp,
+    pub ocp: OcpConfig,
+    pub lna: LnaConfig,
+    pub rx_bw: RxBwConfig,
+    pub afc_bw: RxBwConfig,
+    pub rssi_threshold: u8,
+    pub preamble: u16,
+    pub sync: SyncConfig,
+    pub packet: PacketConfig,
+    pub node_address: u8,
+    pub broadcast_address: u8,
+    pub fifo_mode: FifoMode,
+    /// FIFO threshold of the `FifoLevel` interrupt used with [`FifoMode::NotEmpty`],
+    /// [`FifoMode::Level`] carries its own threshold
+    pub fifo_threshold: u8,
+    /// AES encryption is enabled, the key itself cannot be read back
+    pub aes: bool,
+}
+
+impl RadioConfig {
+    fn from_regs(regs: &[u8; 0x4f]) -> Self {
+        let reg = |r: Registers| regs[r as usize - 1];
+        let reg16 = |r: Registers| u16::from_be_bytes([reg(r), regs[r as usize]]);
+        let modulation = Modulation::from_value(reg(Registers::DataModul));
+        let modulation_type = modulation.modulation_type;
+        let frf = u32::from_be_bytes([
+            0,
+            reg(Registers::FrfMsb),
+            reg(Registers::FrfMid),
+            reg(Registers::FrfLsb),
+        ]);
+        let mut sync = [0u8; 8];
+        let start = Registers::SyncValue1 as usize - 1;
+        sync.copy_from_slice(&regs[start..start + 8]);
+        RadioConfig {
+            modulation,
+            bit_rate: FOSC / f32::from(reg16(Registers::BitrateMsb)),
+            fdev: f32::from(reg16(Registers::FdevMsb)) * FSTEP,
+            frequency: frf as f32 * FSTEP,
+            pa_level: PaLevel::from_value(reg(Registers::PaLevel)),
+            pa_ramp: PaRamp::from_value(reg(Registers::PaRamp)),
+            ocp: OcpConfig::from_value(reg(Registers::Ocp)),
+            lna: LnaConfig::from_value(reg(Registers::Lna)),
+            rx_bw: RxBwConfig::from_value(reg(Registers::RxBw), modulation_type),
+            afc_bw: RxBwConfig::from_value(reg(Registers::AfcBw), modulation_type),
+            rssi_threshold: reg(Registers::RssiThresh),
+            preamble: reg16(Registers::PreambleMsb),
+            sync: SyncConfig::from_value(reg(Registers::SyncConfig), sync),
+            packet: PacketConfig::from_value(
+                reg(Registers::PacketConfig1),
+                reg(Registers::PayloadLength),
+                reg(Registers::PacketConfig2),
+            ),
+            node_address: reg(Registers::NodeAddrs),
+            broadcast_address: reg(Registers::BroadcastAddrs),
+            fifo_mode: FifoMode::from_value(reg(Registers::FifoThresh)),
+            fifo_threshold: reg(Registers::FifoThresh) & 0x7f,
+            aes: reg(Registers::PacketConfig2) & 0x01 != 0,
+        }
+    }
 }
 
 impl<S, D, Espi> Rfm69<NoCs, SpiTransactional<S>, D>
@@ -38,22 +160,101 @@ where
     }
 }
 
-impl<T, S, D, Ecs, Espi> Rfm69<T, S, D>
+impl<S, D, R, Espi> Rfm69<NoCs, SpiTransactional<S>, D, R>
+where
+    S: Transactional<u8, Error = Espi>,
+    D: DelayMs<u8>,
+    R: ResetPin,
+{
+    /// Creates a new instance like [`new_without_cs`](Rfm69::new_without_cs), with a pin
+    /// connected to the reset line of the chip which is used by [`reset`](Self::reset).
+    pub fn new_without_cs_with_reset(spi: S, delay: D, reset: R) -> Self {
+        Self::new_with_reset(SpiTransactional(spi), NoCs, delay, reset)
+    }
+}
+
+impl<T, S, D, Ecs, Espi> Rfm69<T, S, D, NoReset>
 where
     T: OutputPin<Error = Ecs>,
     S: ReadWrite<Error = Espi>,
     D: DelayMs<u8>,
 {
-    /// Creates a new instance with everything set to default values after restart.
+    /// Creates a new instance with everything set to default values after restart. Some of the
+    /// reset values are not the ones recommended by the datasheet, use
+    /// [`recommended_defaults`](crate::recommended_defaults) to apply them.
     pub fn new(spi: S, cs: T, delay: D) -> Self {
+        Self::new_with_reset(spi, cs, delay, NoReset)
+    }
+}
+
+impl<T, S, D, R, Ecs, Espi> Rfm69<T, S, D, R>
+where
+    T: OutputPin<Error = Ecs>,
+    S: ReadWrite<Error = Espi>,
+    D: DelayMs<u8>,
+    R: ResetPin,
+{
+    /// Creates a new instance with everything set to default values after restart, and a pin
+    /// connected to the reset line of the chip which is used by [`reset`](Self::reset).
+    pub fn new_with_reset(spi: S, cs: T, delay: D, reset: R) -> Self {
         Rfm69 {
             spi,
             cs,
             delay,
+            reset,
             mode: Mode::Standby,
             dio: [None; 6],
+            clk_out: ClkOut::Off,
             rssi: 0.0,
+            variant: ModuleVariant::LowPower,
+            pa1_pa2: false,
+            high_power: false,
+            ocp: OcpConfig {
+                enabled: true,
+                trim: 95,
+            },
+            seed: 0x2545_f491,
+            cache: None,
+        }
+    }
+
+    /// Resets the chip by driving the reset pin high for 1 ms and waits 5 ms until the chip is
+    /// ready, as required by the datasheet. The driver state is set back to the defaults after
+    /// restart and the register cache is reloaded when it is enabled. Does nothing without a reset
+    /// pin, the chip keeps its registers then.
+    /// Returns `Reset` if the reset pin cannot be driven.
+    pub fn reset(&mut self) -> Result<(), Ecs, Espi> {
+        if !self.reset.set_reset(true).map_err(|_| Error::Reset)? {
+            return Ok(());
+        }
+        self.delay.delay_ms(1);
+        self.reset.set_reset(false).map_err(|_| Error::Reset)?;
+        self.delay.delay_ms(5);
+
+        self.mode = Mode::Standby;
+        self.dio = [None; 6];
+        self.clk_out = ClkOut::Off;
+        self.rssi = 0.0;
+        self.pa1_pa2 = false;
+        self.high_power = false;
+        self.ocp = OcpConfig {
+            enabled: true,
+            trim: 95,
+        };
+        self.resync()
+    }
+
+    /// Resets the chip when a reset pin is connected and checks that it is present by reading the
+    /// chip version from `RegVersion (0x10)`.
+    /// Returns `UnknownChip` with the version read if it is not 0x24, which usually points to a
+    /// wiring fault.
+    pub fn init(&mut self) -> Result<(), Ecs, Espi> {
+        self.reset()?;
+        let version = self.read(Registers::Version)?;
+        if version != 0x24 {
+            return Err(Error::UnknownChip(version));
         }
+        Ok(())
     }
 
     /// Reads content of all registers that are available.
@@ -63,14 +264,161 @@ where
         Ok(buffer)
     }
 
-    /// Sets the mode in corresponding register `RegOpMode (0x01)`.
+    /// Enables the register cache and fills it from the chip. Reads of cached registers are
+    /// served from the cache, so read-modify-write operations like [`packet`](Self::packet) only
+    /// write over SPI. Volatile registers like the mode, FIFO, IRQ flags, RSSI, AFC/FEI and
+    /// temperature values are always read from the chip.
+    pub fn enable_cache(&mut self) -> Result<(), Ecs, Espi> {
+        self.cache = Some(Cache::new());
+        self.resync()
+    }
+
+    /// Disables the register cache, all reads go to the chip again.
+    pub fn disable_cache(&mut self) {
+        self.cache = None;
+    }
+
+    /// Reloads the register cache from the chip. This is needed when the registers were changed
+    /// without this driver, for example by another driver sharing the chip. Does nothing when the
+    /// cache is disabled.
+    pub fn resync(&mut self) -> Result<(), Ecs, Espi> {
+        match self.cache.as_mut() {
+            Some(cache) => cache.invalidate(),
+            None => return Ok(()),
+        }
+        self.read_all_regs()?;
+        let mut buffer = [0u8; 1];
+        for &reg in &[
+            Registers::TestLna,
+            Registers::TestPa1,
+            Registers::TestPa2,
+            Registers::TestDagc,
+        ] {
+            self.read_many(reg, &mut buffer)?;
+        }
+        Ok(())
+    }
+
+    /// Reads all registers and decodes the current radio configuration.
+    pub fn config(&mut self) -> Result<RadioConfig, Ecs, Espi> {
+        Ok(RadioConfig::from_regs(&self.read_all_regs()?))
+    }
+
+    /// Switches to standby mode and writes the complete radio configuration with a few burst
+    /// writes of the contiguous register ranges. The receive timeouts and automatic modes are
+    /// cleared, the 18 to 20 dBm high power settings are disabled and have to be enabled again
+    /// with [`output_power`](Self::output_power).
+    /// Returns `SyncSize`, `SyncTolerance` or `OcpOutOfRange` for an invalid configuration,
+    /// before writing anything.
+    pub fn apply(&mut self, config: &RadioConfig) -> Result<(), Ecs, Espi> {
+        let sync_len = usize::from(config.sync.len);
+        if sync_len > 8 {
+            return Err(Error::SyncSize);
+        } else if config.sync.tolerance > 7 {
+            return Err(Error::SyncTolerance);
+        }
+        let ocp = config.ocp.value().ok_or(Error::OcpOutOfRange)?;
+
+        if self.mode != Mode::Standby {
+            self.mode(Mode::Standby)?;
+            self.wait_mode_ready()?;
+        }
+
+        let mut radio = [0u8; 8];
+        radio[0] = config.modulation.value();
+        radio[1..3].copy_from_slice(&bit_rate_value(config.bit_rate));
+        radio[3..5].copy_from_slice(&fdev_value(config.fdev));
+        radio[5..8].copy_from_slice(&frequency_value(config.frequency));
+        self.write_many(Registers::DataModul, &radio)?;
+
+        self.write_many(
+            Registers::PaLevel,
+            &[config.pa_level.value(), config.pa_ramp as u8, ocp],
+        )?;
+        self.write_many(
+            Registers::Lna,
+            &[
+                config.lna.value(),
+                config.rx_bw.value(),
+                config.afc_bw.value(),
+            ],
+        )?;
+
+        let mut sync = [0u8; 14];
+        sync[0] = config.rssi_threshold;
+        sync[3..5].copy_from_slice(&config.preamble.to_be_bytes());
+        sync[5] = config.sync.value();
+        sync[6..6 + sync_len].copy_from_slice(&config.sync.value[..sync_len]);
+        self.write_many(Registers::RssiThresh, &sync)?;
+
+        let [config1, len, config2] = config.packet.value();
+        let fifo_thresh = match config.fifo_mode {
+            FifoMode::NotEmpty => 0x80 | (config.fifo_threshold & 0x7f),
+            FifoMode::Level(_) => config.fifo_mode.value(),
+        };
+        self.write_many(
+            Registers::PacketConfig1,
+            &[
+                config1,
+                len,
+                config.node_address,
+                config.broadcast_address,
+                0x00,
+                fifo_thresh,
+                config2 | config.aes as u8,
+            ],
+        )?;
+
+        self.pa1_pa2 = config.pa_level.pa1 && config.pa_level.pa2;
+        self.ocp = config.ocp;
+        if self.high_power {
+            self.high_power = false;
+            self.high_power_regs(self.mode)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the mode in corresponding register `RegOpMode (0x01)`. Leaving [`Mode::Listen`] uses
+    /// the listen abort sequence described in the datasheet.
+    /// Returns `OcpLimit` when entering transmit mode with the over current protection limit
+    /// below the current drawn at the configured output power.
     pub fn mode(&mut self, mode: Mode) -> Result<(), Ecs, Espi> {
-        let val = mode as u8;
-        self.update(Registers::OpMode, |r| (r & 0xe3) | val)?;
+        self.prepare_mode(mode)?;
+        if self.mode == Mode::Listen && mode != Mode::Listen {
+            self.abort_listen(mode)?;
+        } else {
+            let val = mode as u8;
+            self.update(Registers::OpMode, |r| (r & 0xe3) | val)?;
+        }
         self.mode = mode;
         self.dio()
     }
 
+    /// Configures listen mode in corresponding registers `RegListen1-3 (0x0D-0x0F)` and enters it
+    /// from standby mode. Returns `ListenDuration` if any of the durations is too long.
+    pub fn enter_listen(&mut self, config: ListenConfig) -> Result<(), Ecs, Espi> {
+        let regs = config.value().ok_or(Error::ListenDuration)?;
+        self.mode(Mode::Standby)?;
+        self.wait_mode_ready()?;
+        self.write_many(Registers::Listen1, &regs)?;
+        self.mode(Mode::Listen)
+    }
+
+    /// Exits listen mode to standby mode.
+    pub fn exit_listen(&mut self) -> Result<(), Ecs, Espi> {
+        self.mode(Mode::Standby)
+    }
+
+    /// Sets automatic mode transitions in corresponding register `RegAutoModes (0x3B)`.
+    pub fn auto_modes(&mut self, auto_modes: AutoModes) -> Result<(), Ecs, Espi> {
+        self.write(Registers::AutoModes, auto_modes.value())
+    }
+
+    /// Clears automatic mode transitions in corresponding register `RegAutoModes (0x3B)`.
+    pub fn clear_auto_modes(&mut self) -> Result<(), Ecs, Espi> {
+        self.write(Registers::AutoModes, 0x00)
+    }
+
     /// Sets the modulation in corresponding register `RegDataModul (0x02)`.
     pub fn modulation(&mut self, modulation: Modulation) -> Result<(), Ecs, Espi> {
         self.write(Registers::DataModul, modulation.value())
@@ -79,22 +427,19 @@ where
     /// Computes the bitrate, according to `Fosc / bit_rate` and stores it in
     /// `RegBitrateMsb (0x03), RegBitrateLsb (0x04)`.
     pub fn bit_rate(&mut self, bit_rate: f32) -> Result<(), Ecs, Espi> {
-        let reg = (FOSC / bit_rate) as u16;
-        self.write_many(Registers::BitrateMsb, &reg.to_be_bytes())
+        self.write_many(Registers::BitrateMsb, &bit_rate_value(bit_rate))
     }
 
     /// Computes the frequency deviation, according to `fdev / Fstep` and stores it in
     /// `RegFdevMsb (0x05), RegFdevLsb (0x06)`.
     pub fn fdev(&mut self, fdev: f32) -> Result<(), Ecs, Espi> {
-        let reg = (fdev / FSTEP) as u16;
-        self.write_many(Registers::FdevMsb, &reg.to_be_bytes())
+        self.write_many(Registers::FdevMsb, &fdev_value(fdev))
     }
 
     /// Computes the radio frequency, according to `frequency / Fstep` and stores it in
     /// `RegFrfMsb (0x07), RegFrfMid (0x08), RegFrfLsb (0x09)`.
     pub fn frequency(&mut self, frequency: f32) -> Result<(), Ecs, Espi> {
-        let reg = (frequency / FSTEP) as u32;
-        self.write_many(Registers::FrfMsb, &reg.to_be_bytes()[1..])
+        self.write_many(Registers::FrfMsb, &frequency_value(frequency))
     }
 
     /// Stores DIO mapping for different RFM69 modes. For DIO behavior between modes
@@ -126,6 +471,35 @@ where
         self.dio()
     }
 
+    /// Sets the clock output on DIO5 in corresponding register `RegDioMapping2 (0x26)`.
+    pub fn clk_out(&mut self, clk_out: ClkOut) -> Result<(), Ecs, Espi> {
+        self.clk_out = clk_out;
+        self.dio()
+    }
+
+    /// Calibrates the RC oscillator using `RegOsc1 (0x0A)`, which is used for listen mode timing.
+    /// The chip is switched to standby mode for the calibration and the previous mode is restored
+    /// afterwards. Returns `Timeout` if the calibration does not finish in time.
+    pub fn calibrate_rc_oscillator(&mut self) -> Result<(), Ecs, Espi> {
+        let mode = self.mode;
+        if mode != Mode::Standby {
+            self.mode(Mode::Standby)?;
+            self.wait_mode_ready()?;
+        }
+
+        let result = self.run_rc_calibration();
+
+        if mode != Mode::Standby {
+            self.mode(mode)?;
+        }
+        result
+    }
+
+    fn run_rc_calibration(&mut self) -> Result<(), Ecs, Espi> {
+        self.write(Registers::Osc1, 0x80)?;
+        self.with_timeout(100, 5, |rfm| Ok((rfm.read(Registers::Osc1)? & 0x40) != 0))
+    }
+
     /// Sets preamble length in corresponding registers `RegPreambleMsb (0x2C),
     /// RegPreambleLsb (0x2D)`.
     pub fn preamble(&mut self, reg: u16) -> Result<(), Ecs, Espi> {
@@ -133,7 +507,9 @@ where
     }
 
     /// Sets sync config and sync words in `RegSyncConfig (0x2E), RegSyncValue1-8(0x2F-0x36)`.
-    /// Maximal sync length is 8, pass empty buffer to clear the sync flag.
+    /// Maximal sync length is 8, pass empty buffer to clear the sync flag. No bit errors are
+    /// tolerated and the FIFO is filled on `SyncAddress`, use [`sync_config`](Self::sync_config)
+    /// for other settings.
     pub fn sync(&mut self, sync: &[u8]) -> Result<(), Ecs, Espi> {
         let len = sync.len();
         if len == 0 {
@@ -141,28 +517,39 @@ where
         } else if len > 8 {
             return Err(Error::SyncSize);
         }
-        let reg = 0x80 | ((len - 1) as u8) << 3;
-        self.write(Registers::SyncConfig, reg)?;
-        self.write_many(Registers::SyncValue1, sync)
+        let mut value = [0u8; 8];
+        value[..len].copy_from_slice(sync);
+        self.sync_config(SyncConfig {
+            fill_condition: FifoFillCondition::SyncAddress,
+            tolerance: 0,
+            len: len as u8,
+            value,
+        })
+    }
+
+    /// Sets sync config and sync words in `RegSyncConfig (0x2E), RegSyncValue1-8(0x2F-0x36)`.
+    /// Returns `SyncSize` if the sync length is larger than 8 and `SyncTolerance` if the
+    /// tolerance is larger than 7.
+    pub fn sync_config(&mut self, sync: SyncConfig) -> Result<(), Ecs, Espi> {
+        let len = usize::from(sync.len);
+        if len > 8 {
+            return Err(Error::SyncSize);
+        } else if sync.tolerance > 7 {
+            return Err(Error::SyncTolerance);
+        }
+        self.write(Registers::SyncConfig, sync.value())?;
+        if len > 0 {
+            self.write_many(Registers::SyncValue1, &sync.value[..len])?;
+        }
+        Ok(())
     }
 
     /// Sets packet settings in corresponding registers `RegPacketConfig1 (0x37),
     /// RegPayloadLength (0x38), RegPacketConfig2 (0x3D)`.
     pub fn packet(&mut self, packet_config: PacketConfig) -> Result<(), Ecs, Espi> {
-        let len: u8;
-        let mut reg = 0x00;
-        match packet_config.format {
-            PacketFormat::Fixed(size) => len = size,
-            PacketFormat::Variable(size) => {
-                len = size;
-                reg |= 0x80;
-            }
-        }
-        reg |=
-            packet_config.dc as u8 | packet_config.filtering as u8 | (packet_config.crc as u8) << 4;
-        self.write_many(Registers::PacketConfig1, &[reg, len])?;
-        reg = packet_config.interpacket_rx_delay as u8 | (packet_config.auto_rx_restart as u8) << 1;
-        self.update(Registers::PacketConfig2, |r| r & 0x0d | reg)
+        let [config1, len, config2] = packet_config.value();
+        self.write_many(Registers::PacketConfig1, &[config1, len])?;
+        self.update(Registers::PacketConfig2, |r| r & 0x0d | config2)
     }
 
     /// Sets node address in corresponding register `RegNodeAdrs (0x39)`.
@@ -202,6 +589,92 @@ where
         self.rssi
     }
 
+    /// Triggers RSSI measurement in `RegRssiConfig (0x23)` and returns the RSSI in dBm once it is
+    /// done. The chip must be in receive mode. The value returned by [`rssi`](Self::rssi) is
+    /// not affected.
+    pub fn measure_rssi(&mut self) -> Result<f32, Ecs, Espi> {
+        self.write(Registers::RssiConfig, 0x01)?;
+        self.with_timeout(100, 5, |rfm| {
+            Ok((rfm.read(Registers::RssiConfig)? & 0x02) != 0)
+        })?;
+        Ok(self.read(Registers::RssiValue)? as f32 / -2.0)
+    }
+
+    /// Measures RSSI `samples` times in receive mode and returns the mean, minimum and maximum.
+    /// The chip is switched to receive mode if needed and the previous mode is restored
+    /// afterwards.
+    pub fn noise_floor(&mut self, samples: u8) -> Result<NoiseFloor, Ecs, Espi> {
+        let mode = self.mode;
+        if mode != Mode::Receiver {
+            self.mode(Mode::Receiver)?;
+            self.wait_mode_ready()?;
+        }
+
+        let result = self.sample_noise_floor(samples.max(1));
+
+        if mode != Mode::Receiver {
+            self.mode(mode)?;
+        }
+        result
+    }
+
+    fn sample_noise_floor(&mut self, samples: u8) -> Result<NoiseFloor, Ecs, Espi> {
+        let mut floor = NoiseFloor {
+            mean: 0.0,
+            min: f32::MAX,
+            max: f32::MIN,
+        };
+        for _ in 0..samples {
+            let rssi = self.measure_rssi()?;
+            floor.mean += rssi;
+            floor.min = floor.min.min(rssi);
+            floor.max = floor.max.max(rssi);
+        }
+        floor.mean /= samples as f32;
+        Ok(floor)
+    }
+
+    /// Measures the temperature in degrees Celsius using `RegTemp1 (0x4E), RegTemp2 (0x4F)`.
+    /// The sensor needs calibration, `calibration_offset` is added to the result. The chip is
+    /// switched to standby mode for the measurement and the previous mode is restored afterwards.
+    /// Returns `Timeout` if the measurement does not finish in time.
+    pub fn temperature(&mut self, calibration_offset: i8) -> Result<i16, Ecs, Espi> {
+        let mode = self.mode;
+        if mode != Mode::Standby {
+            self.mode(Mode::Standby)?;
+            self.wait_mode_ready()?;
+        }
+
+        let result = self.read_temperature();
+
+        if mode != Mode::Standby {
+            self.mode(mode)?;
+        }
+        // The value decreases by one for every degree, 165 is the uncalibrated offset.
+        Ok(165 - i16::from(result?) + i16::from(calibration_offset))
+    }
+
+    fn read_temperature(&mut self) -> Result<u8, Ecs, Espi> {
+        self.write(Registers::Temp1, 0x08)?;
+        self.with_timeout(100, 5, |rfm| Ok((rfm.read(Registers::Temp1)? & 0x04) == 0))?;
+        self.read(Registers::Temp2)
+    }
+
+    /// Receive a packet from another RFM69 and store its payload in the buffer. The length byte
+    /// of variable length packets and the address byte are not part of the payload, they are
+    /// returned in [`ReceivedPacket`] together with RSSI, FEI and AFC values. This call blocks
+    /// until a packet is available, same as [`recv`](Self::recv).
+    /// Returns `BufferTooSmall` and discards the packet if the payload does not fit the buffer.
+    pub fn recv_packet(&mut self, buffer: &mut [u8]) -> Result<ReceivedPacket, Ecs, Espi> {
+        self.mode(Mode::Receiver)?;
+        self.wait_mode_ready()?;
+
+        while !self.is_packet_ready()? {}
+
+        self.mode(Mode::Standby)?;
+        self.read_packet(buffer)
+    }
+
     /// Receive bytes from another RFM69. This call blocks until there are any
     /// bytes available. This can be combined with DIO interrupt for `PayloadReady`, calling
     /// `recv` immediately after the interrupt should not block.
@@ -221,6 +694,31 @@ where
         Ok(())
     }
 
+    /// Receive bytes from another RFM69, waiting at most `timeout` milliseconds. The hardware
+    /// timeouts in `RegRxTimeout1 (0x2A), RegRxTimeout2 (0x2B)` restart the receiver when no
+    /// signal above `RssiThreshold` is detected within `timeout`, or when a detected packet does
+    /// not become ready within twice the air time of the longest payload. Returns `RxTimeout`
+    /// when no packet was received before the timeout expired.
+    pub fn recv_timeout(&mut self, buffer: &mut [u8], timeout: u32) -> Result<(), Ecs, Espi> {
+        self.recv_deadline(buffer, timeout).map(|_| ())
+    }
+
+    /// Receive bytes from another RFM69 using `RegAutoModes (0x3B)`. The chip stays in receive
+    /// mode, switches to standby when the payload is ready and returns back to receive mode once
+    /// the FIFO is emptied, so no mode switches are needed between packets. This call blocks until
+    /// there are any bytes available. Bytes that do not fit into the buffer are discarded.
+    /// The auto modes are cleared afterwards and the chip stays in receive mode.
+    pub fn recv_auto(&mut self, buffer: &mut [u8]) -> Result<(), Ecs, Espi> {
+        self.auto_modes(AutoModes {
+            enter: EnterCondition::PayloadReady,
+            exit: ExitCondition::FifoEmpty,
+            intermediate: IntermediateMode::Standby,
+        })?;
+        let result = self.read_auto(buffer);
+        self.clear_auto_modes()?;
+        result
+    }
+
     /// Receive bytes from another RFM69. This call blocks until there are any
     /// bytes available. This can be combined with DIO interrupt for `SyncAddressMatch`, calling
     /// `recv_large` immediately after the interrupt will not block waiting for packets. It will
@@ -261,21 +759,67 @@ where
     }
 
     /// Send bytes to another RFM69. This can block until all data are send.
+    /// The transmission is sequenced by the chip from standby mode, see
+    /// [`send_auto`](Self::send_auto).
     pub fn send(&mut self, buffer: &[u8]) -> Result<(), Ecs, Espi> {
+        self.send_auto(buffer, Mode::Standby)
+    }
+
+    /// Send bytes to another RFM69 when the channel is free. The RSSI is measured in receive mode
+    /// and compared against the threshold, while the channel is busy the send is retried after a
+    /// random backoff within an exponentially growing window.
+    /// Returns `ChannelBusy` if the channel is still busy after all retries.
+    pub fn send_csma(&mut self, buffer: &[u8], csma: &CsmaConfig) -> Result<(), Ecs, Espi> {
+        let mut window = u32::from(csma.backoff.max(1));
+        for retry in 0..=csma.retries {
+            if self.measure_channel()? <= csma.threshold {
+                return self.send(buffer);
+            }
+            if retry < csma.retries {
+                let backoff = self.random() % window;
+                self.delay_ms(backoff);
+                window = (window * 2).min(u32::from(csma.max_backoff.max(1)));
+            }
+        }
+
+        self.mode(Mode::Standby)?;
+        Err(Error::ChannelBusy)
+    }
+
+    /// Send bytes to another RFM69 using `RegAutoModes (0x3B)`. The chip is put into `mode`,
+    /// which should be either `Sleep` or `Standby`, enters transmit mode as soon as the FIFO is
+    /// not empty and returns back to `mode` after `PacketSent`. This can block until all data
+    /// are send. The auto modes are cleared afterwards.
+    pub fn send_auto(&mut self, buffer: &[u8], mode: Mode) -> Result<(), Ecs, Espi> {
         if buffer.is_empty() {
             return Ok(());
         }
 
-        self.mode(Mode::Standby)?;
-        self.wait_mode_ready()?;
+        if self.mode != mode {
+            self.mode(mode)?;
+            self.wait_mode_ready()?;
+        }
+        self.prepare_mode(Mode::Transmitter)?;
+        let dio = self.dio_value(Mode::Transmitter);
+        let idle_dio = self.dio_value(mode);
+        if dio != idle_dio {
+            self.write_many(Registers::DioMapping1, &dio.to_be_bytes())?;
+        }
 
         self.reset_fifo()?;
-
+        self.auto_modes(AutoModes {
+            enter: EnterCondition::FifoNotEmpty,
+            exit: ExitCondition::PacketSent,
+            intermediate: IntermediateMode::Transmitter,
+        })?;
         self.write_many(Registers::Fifo, buffer)?;
-        self.mode(Mode::Transmitter)?;
-        self.wait_packet_sent()?;
+        self.wait_auto_mode_done()?;
+        self.clear_auto_modes()?;
 
-        self.mode(Mode::Standby)
+        if dio != idle_dio {
+            self.dio()?;
+        }
+        Ok(())
     }
 
     /// Send bytes to another RFM69. This will block until all data are send.
@@ -326,10 +870,34 @@ where
         Ok(self.read(Registers::IrqFlags2)? & 0x04 != 0)
     }
 
+    /// Configure the low battery detector in corresponding register `RegLowBat (0x0C)`.
+    /// The `LowBat` event can be mapped to a DIO pin with [`DioMapping::low_bat`].
+    pub fn low_bat(&mut self, low_bat: LowBatConfig) -> Result<(), Ecs, Espi> {
+        self.write(Registers::LowBat, low_bat.value())
+    }
+
+    /// Check if `LowBatMonitor` in `RegLowBat (0x0C)` is set, which means the supply voltage is
+    /// below the configured trim.
+    pub fn low_battery(&mut self) -> Result<bool, Ecs, Espi> {
+        Ok(self.read(Registers::LowBat)? & 0x10 != 0)
+    }
+
     /// Configure LNA in corresponding register `RegLna (0x18)`.
     pub fn lna(&mut self, lna: LnaConfig) -> Result<(), Ecs, Espi> {
-        let reg = (lna.zin as u8) | (lna.gain_select as u8);
-        self.update(Registers::Lna, |r| (r & 0x78) | reg)
+        self.update(Registers::Lna, |r| (r & 0x78) | lna.value())
+    }
+
+    /// Reads the LNA gain currently selected by the AGC from `LnaCurrentGain` in
+    /// `RegLna (0x18)`.
+    pub fn current_lna_gain(&mut self) -> Result<LnaGain, Ecs, Espi> {
+        let mut reg = [0u8; 1];
+        self.read_many(Registers::Lna, &mut reg)?;
+        Ok(LnaGain::from_value(reg[0] >> 3))
+    }
+
+    /// Configure AGC in corresponding registers `RegAgcRef (0x14), RegAgcThresh1-3 (0x15-0x17)`.
+    pub fn agc(&mut self, agc: AgcConfig) -> Result<(), Ecs, Espi> {
+        self.write_many(Registers::AgcRef, &agc.value())
     }
 
     /// Configure RSSI Threshold in corresponding register `RegRssiThresh (0x29)`.
@@ -352,6 +920,97 @@ where
         self.write(Registers::TestPa2, pa13 as u8)
     }
 
+    /// Sets the power amplifier configuration in corresponding register `RegPaLevel (0x11)`.
+    pub fn pa_level(&mut self, pa_level: PaLevel) -> Result<(), Ecs, Espi> {
+        self.write(Registers::PaLevel, pa_level.value())?;
+        self.pa1_pa2 = pa_level.pa1 && pa_level.pa2;
+        Ok(())
+    }
+
+    /// Sets the rise/fall time of ramp up/down in FSK in corresponding register
+    /// `RegPaRamp (0x12)`.
+    pub fn pa_ramp(&mut self, ramp: PaRamp) -> Result<(), Ecs, Espi> {
+        self.write(Registers::PaRamp, ramp as u8)
+    }
+
+    /// Reads the rise/fall time of ramp up/down in FSK from register `RegPaRamp (0x12)`.
+    pub fn read_pa_ramp(&mut self) -> Result<PaRamp, Ecs, Espi> {
+        Ok(PaRamp::from_value(self.read(Registers::PaRamp)?))
+    }
+
+    /// Sets the over current protection in corresponding register `RegOcp (0x13)`.
+    /// Returns `OcpOutOfRange` if the trim is not between 45 and 120 mA or not a multiple of 5 mA.
+    /// The protection should be disabled for the 18 to 20 dBm output power settings.
+    pub fn ocp(&mut self, ocp: OcpConfig) -> Result<(), Ecs, Espi> {
+        let reg = ocp.value().ok_or(Error::OcpOutOfRange)?;
+        self.write(Registers::Ocp, reg)?;
+        self.ocp = ocp;
+        Ok(())
+    }
+
+    /// Reads the over current protection from register `RegOcp (0x13)`.
+    pub fn read_ocp(&mut self) -> Result<OcpConfig, Ecs, Espi> {
+        self.ocp = OcpConfig::from_value(self.read(Registers::Ocp)?);
+        Ok(self.ocp)
+    }
+
+    /// Sets the module variant that is used by [`output_power`](Self::output_power) to select the
+    /// power amplifiers. Defaults to [`ModuleVariant::LowPower`].
+    pub fn module_variant(&mut self, variant: ModuleVariant) {
+        self.variant = variant;
+    }
+
+    /// Sets the output power in dBm, selecting the power amplifiers according to the module
+    /// variant. Low power modules use PA0 for -18 to 13 dBm. High power modules use PA1 for -2 to
+    /// 13 dBm, PA1 and PA2 for 14 to 17 dBm and PA1 and PA2 with the high power settings in
+    /// `RegTestPa1 (0x5A), RegTestPa2 (0x5C)` for 18 to 20 dBm. The high power settings are
+    /// enabled when entering transmit mode and disabled when entering receive mode.
+    /// Returns `PowerOutOfRange` if the module variant cannot output the requested power.
+    pub fn output_power(&mut self, dbm: i8) -> Result<(), Ecs, Espi> {
+        let (pa_level, high_power) = match (self.variant, dbm) {
+            (ModuleVariant::LowPower, -18..=13) => (
+                PaLevel {
+                    pa0: true,
+                    pa1: false,
+                    pa2: false,
+                    output_power: (dbm + 18) as u8,
+                },
+                false,
+            ),
+            (ModuleVariant::HighPower, -2..=13) => (
+                PaLevel {
+                    pa0: false,
+                    pa1: true,
+                    pa2: false,
+                    output_power: (dbm + 18) as u8,
+                },
+                false,
+            ),
+            (ModuleVariant::HighPower, 14..=17) => (
+                PaLevel {
+                    pa0: false,
+                    pa1: true,
+                    pa2: true,
+                    output_power: (dbm + 14) as u8,
+                },
+                false,
+            ),
+            (ModuleVariant::HighPower, 18..=20) => (
+                PaLevel {
+                    pa0: false,
+                    pa1: true,
+                    pa2: true,
+                    output_power: (dbm + 11) as u8,
+                },
+                true,
+            ),
+            _ => return Err(Error::PowerOutOfRange),
+        };
+        self.pa_level(pa_level)?;
+        self.high_power = high_power;
+        self.high_power_regs(self.mode)
+    }
+
     /// Configure Continuous Dagc in corresponding register `RegTestDagc (0x6F)`.
     pub fn continuous_dagc(&mut self, cdagc: ContinuousDagc) -> Result<(), Ecs, Espi> {
         self.write(Registers::TestDagc, cdagc as u8)
@@ -362,10 +1021,7 @@ where
     where
         RxBwT: RxBwFreq,
     {
-        self.write(
-            Registers::RxBw,
-            rx_bw.dcc_cutoff as u8 | rx_bw.rx_bw.value(),
-        )
+        self.write(Registers::RxBw, rx_bw.value())
     }
 
     /// Configure Rx AFC Bandwidth in corresponding register `RegAfcBw (0x1A)`.
@@ -373,10 +1029,60 @@ where
     where
         RxBwT: RxBwFreq,
     {
-        self.write(
-            Registers::AfcBw,
-            rx_bw.dcc_cutoff as u8 | rx_bw.rx_bw.value(),
-        )
+        self.write(Registers::AfcBw, rx_bw.value())
+    }
+
+    /// Configure OOK demodulator threshold in corresponding registers `RegOokPeak (0x1B),
+    /// RegOokAvg (0x1C), RegOokFix (0x1D)`.
+    pub fn ook_threshold(&mut self, threshold: OokThreshold) -> Result<(), Ecs, Espi> {
+        self.write_many(Registers::OokPeak, &threshold.value())
+    }
+
+    /// Configure automatic AFC in corresponding register `RegAfcFei (0x1E)`.
+    pub fn afc(&mut self, afc: AfcConfig) -> Result<(), Ecs, Espi> {
+        self.write(Registers::AfcFei, afc.value())
+    }
+
+    /// Configure AFC routine in corresponding register `RegAfcCtrl (0x0B)`. The improved low beta
+    /// routine should be used with modulation index below 2, together with the matching
+    /// [`ContinuousDagc`] setting.
+    pub fn afc_mode(&mut self, mode: AfcMode) -> Result<(), Ecs, Espi> {
+        self.write(Registers::AfcCtrl, mode as u8)
+    }
+
+    /// Triggers AFC in `RegAfcFei (0x1E)` and returns the applied correction in Hz once it is
+    /// done. The chip must be in receive mode.
+    pub fn measure_afc(&mut self) -> Result<f32, Ecs, Espi> {
+        self.update(Registers::AfcFei, |r| (r & 0x0c) | 0x01)?;
+        self.with_timeout(100, 5, |rfm| Ok((rfm.read(Registers::AfcFei)? & 0x10) != 0))?;
+        self.afc_value()
+    }
+
+    /// Triggers FEI measurement in `RegAfcFei (0x1E)` and returns the measured frequency error
+    /// in Hz once it is done. The chip must be in receive mode.
+    pub fn measure_fei(&mut self) -> Result<f32, Ecs, Espi> {
+        self.update(Registers::AfcFei, |r| (r & 0x0c) | 0x20)?;
+        self.with_timeout(100, 5, |rfm| Ok((rfm.read(Registers::AfcFei)? & 0x40) != 0))?;
+        self.fei_value()
+    }
+
+    /// Clears the AFC correction in `RegAfcFei (0x1E)`.
+    pub fn clear_afc(&mut self) -> Result<(), Ecs, Espi> {
+        self.update(Registers::AfcFei, |r| (r & 0x0c) | 0x02)
+    }
+
+    /// Reads the AFC correction in Hz from `RegAfcMsb (0x1F), RegAfcLsb (0x20)`.
+    pub fn afc_value(&mut self) -> Result<f32, Ecs, Espi> {
+        let mut reg = [0u8; 2];
+        self.read_many(Registers::AfcMsb, &mut reg)?;
+        Ok(i16::from_be_bytes(reg) as f32 * FSTEP)
+    }
+
+    /// Reads the measured frequency error in Hz from `RegFeiMsb (0x21), RegFeiLsb (0x22)`.
+    pub fn fei_value(&mut self) -> Result<f32, Ecs, Espi> {
+        let mut reg = [0u8; 2];
+        self.read_many(Registers::FeiMsb, &mut reg)?;
+        Ok(i16::from_be_bytes(reg) as f32 * FSTEP)
     }
 
     /// Direct write to RFM69 registers.
@@ -387,20 +1093,172 @@ where
     /// Direct write to RFM69 registers.
     pub fn write_many(&mut self, reg: Registers, data: &[u8]) -> Result<(), Ecs, Espi> {
         let _guard = CsGuard::new(&mut self.cs)?;
-        self.spi.write_many(reg, data).map_err(Error::Spi)
+        self.spi.write_many(reg, data).map_err(Error::Spi)?;
+        if let Some(cache) = self.cache.as_mut() {
+            cache.store(reg, data);
+        }
+        Ok(())
     }
 
-    /// Direct read from RFM69 registers.
+    /// Read from RFM69 registers, served from the register cache when it is enabled.
     pub fn read(&mut self, reg: Registers) -> Result<u8, Ecs, Espi> {
+        if let Some(value) = self.cache.as_ref().and_then(|cache| cache.get(reg)) {
+            return Ok(value);
+        }
         let mut buffer = [0u8; 1];
         self.read_many(reg, &mut buffer)?;
         Ok(buffer[0])
     }
 
-    /// Direct read from RFM69 registers.
+    /// Direct read from RFM69 registers, this always reads from the chip.
     pub fn read_many(&mut self, reg: Registers, buffer: &mut [u8]) -> Result<(), Ecs, Espi> {
         let _guard = CsGuard::new(&mut self.cs)?;
-        self.spi.read_many(reg, buffer).map_err(Error::Spi)
+        self.spi.read_many(reg, buffer).map_err(Error::Spi)?;
+        if let Some(cache) = self.cache.as_mut() {
+            cache.store(reg, buffer);
+        }
+        Ok(())
+    }
+
+    /// Receives with a timeout like [`recv_timeout`](Self::recv_timeout) and returns the number
+    /// of milliseconds it took.
+    pub(crate) fn recv_deadline(
+        &mut self,
+        buffer: &mut [u8],
+        timeout: u32,
+    ) -> Result<u32, Ecs, Espi> {
+        if buffer.is_empty() {
+            return Ok(0);
+        }
+
+        // The timeouts are in units of 16 bit periods, a bit period is `RegBitrate / 32` us.
+        let mut bit_rate = [0u8; 2];
+        self.read_many(Registers::BitrateMsb, &mut bit_rate)?;
+        let unit = (u32::from(u16::from_be_bytes(bit_rate)) / 2).max(1);
+        let rx_start = (timeout.saturating_mul(1000) / unit).clamp(1, 255) as u8;
+        // Maximal payload with 16 bytes of preamble, sync, address and CRC sent twice.
+        let rssi_thresh = (u16::from(self.read(Registers::PayloadLength)?) + 16).min(255) as u8;
+        self.write_many(Registers::RxTimeout1, &[rx_start, rssi_thresh])?;
+
+        self.mode(Mode::Receiver)?;
+        self.wait_mode_ready()?;
+
+        let mut elapsed = 0;
+        let result = loop {
+            let mut flags = [0u8; 2];
+            self.read_many(Registers::IrqFlags1, &mut flags)?;
+            if flags[1] & 0x04 != 0 {
+                break Ok(elapsed);
+            }
+            if elapsed >= timeout {
+                break Err(Error::RxTimeout);
+            }
+            if flags[0] & 0x04 != 0 {
+                self.mode(Mode::Standby)?;
+                self.mode(Mode::Receiver)?;
+            }
+            self.delay.delay_ms(1);
+            elapsed += 1;
+        };
+
+        self.mode(Mode::Standby)?;
+        self.write_many(Registers::RxTimeout1, &[0, 0])?;
+        let elapsed = result?;
+        self.read_many(Registers::Fifo, buffer)?;
+        self.rssi = self.read(Registers::RssiValue)? as f32 / -2.0;
+        Ok(elapsed)
+    }
+
+    pub(crate) fn read_packet(&mut self, buffer: &mut [u8]) -> Result<ReceivedPacket, Ecs, Espi> {
+        let mut config = [0u8; 2];
+        self.read_many(Registers::PacketConfig1, &mut config)?;
+        let mut len: usize = if config[0] & 0x80 != 0 {
+            self.read(Registers::Fifo)?.into()
+        } else {
+            config[1].into()
+        };
+        let address = if config[0] & 0x06 != 0 {
+            len = len.saturating_sub(1);
+            Some(self.read(Registers::Fifo)?)
+        } else {
+            None
+        };
+
+        self.rssi = self.read(Registers::RssiValue)? as f32 / -2.0;
+        let mut offsets = [0u8; 4];
+        self.read_many(Registers::AfcMsb, &mut offsets)?;
+        let afc = i16::from_be_bytes([offsets[0], offsets[1]]) as f32 * FSTEP;
+        let fei = i16::from_be_bytes([offsets[2], offsets[3]]) as f32 * FSTEP;
+
+        if len > buffer.len() {
+            self.reset_fifo()?;
+            return Err(Error::BufferTooSmall);
+        }
+        self.read_many(Registers::Fifo, &mut buffer[..len])?;
+
+        Ok(ReceivedPacket {
+            len,
+            address,
+            rssi: self.rssi,
+            fei,
+            afc,
+        })
+    }
+
+    fn read_auto(&mut self, buffer: &mut [u8]) -> Result<(), Ecs, Espi> {
+        if self.mode != Mode::Receiver {
+            self.mode(Mode::Receiver)?;
+            self.wait_mode_ready()?;
+        }
+
+        while !self.is_packet_ready()? {}
+
+        self.rssi = self.read(Registers::RssiValue)? as f32 / -2.0;
+        if !buffer.is_empty() {
+            self.read_many(Registers::Fifo, buffer)?;
+        }
+        while !self.is_fifo_empty()? {
+            self.read(Registers::Fifo)?;
+        }
+        Ok(())
+    }
+
+    /// Returns a pseudo random number, the generator is seeded by the measured RSSI values and
+    /// the values passed to [`seed_random`](Self::seed_random).
+    pub(crate) fn random(&mut self) -> u32 {
+        // xorshift32
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        self.seed
+    }
+
+    pub(crate) fn delay_ms(&mut self, ms: u32) {
+        let mut remaining = ms;
+        while remaining > 0 {
+            let step = remaining.min(u32::from(u8::MAX));
+            self.delay.delay_ms(step as u8);
+            remaining -= step;
+        }
+    }
+
+    /// Mixes a value into the state of the pseudo random number generator.
+    pub(crate) fn seed_random(&mut self, value: u32) {
+        self.seed = self.seed.rotate_left(8) ^ value;
+        if self.seed == 0 {
+            self.seed = 0x2545_f491;
+        }
+    }
+
+    /// Measures the RSSI in receive mode and mixes it into the pseudo random number generator.
+    pub(crate) fn measure_channel(&mut self) -> Result<f32, Ecs, Espi> {
+        if self.mode != Mode::Receiver {
+            self.mode(Mode::Receiver)?;
+            self.wait_mode_ready()?;
+        }
+        let rssi = self.measure_rssi()?;
+        self.seed_random((rssi * -2.0) as u32);
+        Ok(rssi)
     }
 
     pub(crate) fn wait_mode_ready(&mut self) -> Result<(), Ecs, Espi> {
@@ -415,23 +1273,85 @@ where
         })
     }
 
+    /// Waits until the packet of an automatic transmission is sent.
+    pub(crate) fn wait_auto_mode_done(&mut self) -> Result<(), Ecs, Espi> {
+        self.with_timeout(100, 5, |rfm| {
+            let mut flags = [0u8; 2];
+            rfm.read_many(Registers::IrqFlags1, &mut flags)?;
+            // PacketSent is cleared when the sequencer leaves transmit mode. The sequencer is
+            // entered on the FifoNotEmpty rising edge, so an empty FIFO outside of the
+            // intermediate mode means the packet was already sent.
+            let packet_sent = flags[1] & 0x08 != 0;
+            let idle = flags[0] & 0x02 == 0 && flags[1] & 0x40 == 0;
+            Ok(packet_sent || idle)
+        })
+    }
+
     fn dio(&mut self) -> Result<(), Ecs, Espi> {
-        let mut reg = 0x07;
+        let reg = self.dio_value(self.mode).to_be_bytes();
+        if let Some(cache) = self.cache.as_ref() {
+            if cache.get(Registers::DioMapping1) == Some(reg[0])
+                && cache.get(Registers::DioMapping2) == Some(reg[1])
+            {
+                return Ok(());
+            }
+        }
+        self.write_many(Registers::DioMapping1, &reg)
+    }
+
+    fn dio_value(&self, mode: Mode) -> u16 {
+        let mut reg = self.clk_out as u16;
         for mapping in self.dio.iter().flatten() {
-            if mapping.dio_mode.eq(self.mode) {
+            if mapping.dio_mode.eq(mode) {
                 reg |= (mapping.dio_type as u16) << (mapping.pin as u16);
             }
         }
-        self.write_many(Registers::DioMapping1, &reg.to_be_bytes())
+        reg
+    }
+
+    fn prepare_mode(&mut self, mode: Mode) -> Result<(), Ecs, Espi> {
+        if mode == Mode::Transmitter && self.ocp.enabled && self.ocp.trim < self.pa_current() {
+            return Err(Error::OcpLimit);
+        }
+        if self.high_power && mode != Mode::Sleep && mode != Mode::Standby {
+            self.high_power_regs(mode)?;
+        }
+        Ok(())
+    }
+
+    fn abort_listen(&mut self, mode: Mode) -> Result<(), Ecs, Espi> {
+        let reg = (self.read(Registers::OpMode)? & 0x83) | mode as u8;
+        self.write(Registers::OpMode, reg | 0x20)?;
+        self.write(Registers::OpMode, reg)
+    }
+
+    fn pa_current(&self) -> u8 {
+        if self.high_power {
+            130
+        } else if self.pa1_pa2 {
+            95
+        } else {
+            45
+        }
+    }
+
+    fn high_power_regs(&mut self, mode: Mode) -> Result<(), Ecs, Espi> {
+        if self.high_power && mode == Mode::Transmitter {
+            self.pa13_dbm1(Pa13dBm1::High20dBm)?;
+            self.pa13_dbm2(Pa13dBm2::High20dBm)
+        } else {
+            self.pa13_dbm1(Pa13dBm1::Normal)?;
+            self.pa13_dbm2(Pa13dBm2::Normal)
+        }
     }
 
     fn reset_fifo(&mut self) -> Result<(), Ecs, Espi> {
         self.write(Registers::IrqFlags2, 0x10)
     }
 
-    fn with_timeout<F>(&mut self, timeout: u8, step: u8, func: F) -> Result<(), Ecs, Espi>
+    fn with_timeout<F>(&mut self, timeout: u8, step: u8, mut func: F) -> Result<(), Ecs, Espi>
     where
-        F: Fn(&mut Rfm69<T, S, D>) -> Result<bool, Ecs, Espi>,
+        F: FnMut(&mut Self) -> Result<bool, Ecs, Espi>,
     {
         let mut done = func(self)?;
         let mut count = 0;
@@ -454,3 +1374,16 @@ where
         self.write(reg, f(val))
     }
 }
+
+fn bit_rate_value(bit_rate: f32) -> [u8; 2] {
+    ((FOSC / bit_rate) as u16).to_be_bytes()
+}
+
+fn fdev_value(fdev: f32) -> [u8; 2] {
+    ((fdev / FSTEP) as u16).to_be_bytes()
+}
+
+fn frequency_value(frequency: f32) -> [u8; 3] {
+    let reg = ((frequency / FSTEP) as u32).to_be_bytes();
+    [reg[1], reg[2], reg[3]]
+}
@@ -0,0 +1,239 @@
+//! Acknowledged and retransmitted datagrams compatible with `RHReliableDatagram` of the Arduino
+//! [RadioHead](https://www.airspayce.com/mikem/arduino/RadioHead/) library, on top of the
+//! [`rh_rf69`](crate::rh_rf69) framing. Every datagram sent to a node carries a new sequence
+//! number as header id and is acknowledged by the receiver with a one byte frame that has the
+//! same id and the [`FLAGS_ACK`] flag. Retransmissions carry the [`FLAGS_RETRY`] flag. Duplicates
+//! are suppressed with the last id seen from each node.
+
+use embedded_hal::blocking::delay::DelayMs;
+use embedded_hal::digital::v2::OutputPin;
+
+use crate::cs::ResetPin;
+use crate::error::{Error, Result};
+use crate::rh_rf69::{Filter, Frame, Header, BROADCAST_ADDRESS, HEADER_LEN, MAX_MESSAGE_LEN};
+use crate::rw::ReadWrite;
+use crate::Rfm69;
+
+/// Header flag of acknowledgement frames.
+pub const FLAGS_ACK: u8 = 0x80;
+/// Header flag of retransmitted datagrams, cleared on the first transmission.
+pub const FLAGS_RETRY: u8 = 0x40;
+
+/// Payload of acknowledgement frames.
+const ACK: u8 = b'!';
+
+/// State of a reliable datagram node, defaults to 3 retries and 200 ms timeout like RadioHead.
+pub struct ReliableDatagram {
+    address: u8,
+    retries: u8,
+    timeout: u32,
+    last_sequence: u8,
+    seen_ids: [u8; 256],
+    retransmissions: u32,
+    seeded: bool,
+}
+
+impl ReliableDatagram {
+    /// Creates a new node with the given address.
+    pub fn new(address: u8) -> Self {
+        ReliableDatagram {
+            address,
+            retries: 3,
+            timeout: 200,
+            last_sequence: 0,
+            seen_ids: [0; 256],
+            retransmissions: 0,
+            seeded: false,
+        }
+    }
+
+    /// Address of this node.
+    pub fn address(&self) -> u8 {
+        self.address
+    }
+
+    /// Sets the number of retransmissions when no acknowledgement is received.
+    pub fn retries(&mut self, retries: u8) {
+        self.retries = retries;
+    }
+
+    /// Sets the minimal time in milliseconds to wait for an acknowledgement. The actual time is
+    /// randomized between `timeout` and twice the `timeout`.
+    pub fn timeout(&mut self, timeout: u32) {
+        self.timeout = timeout;
+    }
+
+    /// Number of retransmissions since creation or the last reset.
+    pub fn retransmissions(&self) -> u32 {
+        self.retransmissions
+    }
+
+    /// Resets the number of retransmissions.
+    pub fn reset_retransmissions(&mut self) {
+        self.retransmissions = 0;
+    }
+
+    /// Sends the payload to the node and waits for the acknowledgement, retransmitting it when
+    /// the acknowledgement does not arrive in time. Datagrams to [`BROADCAST_ADDRESS`] are sent
+    /// once and not acknowledged. Repeated datagrams that were received before are acknowledged
+    /// again while waiting. The first call measures the RSSI to seed the random timeouts.
+    /// Returns `NoAck` if no acknowledgement was received for all retries.
+    pub fn sendto_wait<T, S, D, R, Ecs, Espi>(
+        &mut self,
+        rfm: &mut Rfm69<T, S, D, R>,
+        payload: &[u8],
+        to: u8,
+    ) -> Result<(), Ecs, Espi>
+    where
+        T: OutputPin<Error = Ecs>,
+        S: ReadWrite<Error = Espi>,
+        D: DelayMs<u8>,
+        R: ResetPin,
+    {
+        self.last_sequence = self.last_sequence.wrapping_add(1);
+        let mut header = Header {
+            to,
+            from: self.address,
+            id: self.last_sequence,
+            flags: 0,
+        };
+        if to == BROADCAST_ADDRESS {
+            return rfm.send_rh(header, payload);
+        }
+
+        if !self.seeded {
+            // Nodes sharing the same timeout sequence would keep colliding on retransmissions.
+            rfm.measure_channel()?;
+            rfm.seed_random(u32::from(self.address));
+            self.seeded = true;
+        }
+        let mut buffer = [0u8; 1 + HEADER_LEN + MAX_MESSAGE_LEN];
+        for attempt in 0..=self.retries {
+            if attempt > 0 {
+                header.flags = FLAGS_RETRY;
+                self.retransmissions += 1;
+            }
+            rfm.send_rh(header, payload)?;
+
+            let jitter = u64::from(self.timeout) * u64::from(rfm.random() % 256) / 256;
+            let timeout = self.timeout.saturating_add(jitter as u32);
+            let mut elapsed = 0;
+            while elapsed < timeout {
+                let filter = Filter::Address(self.address);
+                match rfm.recv_rh_deadline(filter, &mut buffer, timeout - elapsed) {
+                    Ok(time) => elapsed += time,
+                    Err(Error::RxTimeout) => break,
+                    Err(e) => return Err(e),
+                }
+                let received = Frame::decode(&buffer)?.header;
+                if received.flags & FLAGS_ACK != 0 {
+                    if received.from == to
+                        && received.to == self.address
+                        && received.id == header.id
+                    {
+                        return Ok(());
+                    }
+                } else if received.id == self.seen_ids[usize::from(received.from)] {
+                    self.acknowledge(rfm, received.id, received.from)?;
+                }
+            }
+        }
+        Err(Error::NoAck)
+    }
+
+    /// Receives the next new datagram sent to this node or broadcasted. Datagrams sent to this
+    /// node are acknowledged, duplicates are acknowledged again and dropped.
+    pub fn recvfrom_ack<'b, T, S, D, R, Ecs, Espi>(
+        &mut self,
+        rfm: &mut Rfm69<T, S, D, R>,
+        buffer: &'b mut [u8],
+    ) -> Result<Frame<'b>, Ecs, Espi>
+    where
+        T: OutputPin<Error = Ecs>,
+        S: ReadWrite<Error = Espi>,
+        D: DelayMs<u8>,
+        R: ResetPin,
+    {
+        loop {
+            let header = rfm.recv_rh(Filter::Address(self.address), buffer)?.header;
+            if self.accept(rfm, header)? {
+                break;
+            }
+        }
+        Ok(Frame::decode(buffer)?)
+    }
+
+    /// Receives like [`recvfrom_ack`](Self::recvfrom_ack), waiting at most `timeout`
+    /// milliseconds for a new datagram.
+    /// Returns `RxTimeout` when no new datagram was received before the timeout expired.
+    pub fn recvfrom_ack_timeout<'b, T, S, D, R, Ecs, Espi>(
+        &mut self,
+        rfm: &mut Rfm69<T, S, D, R>,
+        buffer: &'b mut [u8],
+        timeout: u32,
+    ) -> Result<Frame<'b>, Ecs, Espi>
+    where
+        T: OutputPin<Error = Ecs>,
+        S: ReadWrite<Error = Espi>,
+        D: DelayMs<u8>,
+        R: ResetPin,
+    {
+        let mut elapsed = 0;
+        loop {
+            let filter = Filter::Address(self.address);
+            elapsed += rfm.recv_rh_deadline(filter, buffer, timeout.saturating_sub(elapsed))?;
+            let header = Frame::decode(buffer)?.header;
+            if self.accept(rfm, header)? {
+                break;
+            }
+        }
+        Ok(Frame::decode(buffer)?)
+    }
+
+    /// Acknowledges a received datagram and returns whether it was not seen before.
+    fn accept<T, S, D, R, Ecs, Espi>(
+        &mut self,
+        rfm: &mut Rfm69<T, S, D, R>,
+        header: Header,
+    ) -> Result<bool, Ecs, Espi>
+    where
+        T: OutputPin<Error = Ecs>,
+        S: ReadWrite<Error = Espi>,
+        D: DelayMs<u8>,
+        R: ResetPin,
+    {
+        if header.flags & FLAGS_ACK != 0 {
+            return Ok(false);
+        }
+        if header.to == self.address {
+            self.acknowledge(rfm, header.id, header.from)?;
+        }
+        let seen = &mut self.seen_ids[usize::from(header.from)];
+        if header.id == *seen {
+            return Ok(false);
+        }
+        *seen = header.id;
+        Ok(true)
+    }
+
+    fn acknowledge<T, S, D, R, Ecs, Espi>(
+        &mut self,
+        rfm: &mut Rfm69<T, S, D, R>,
+        id: u8,
+        to: u8,
+    ) -> Result<(), Ecs, Espi>
+    where
+        T: OutputPin<Error = Ecs>,
+        S: ReadWrite<Error = Espi>,
+        D: DelayMs<u8>,
+        R: ResetPin,
+    {
+        let header = Header {
+            to,
+            from: self.address,
+            id,
+            flags: FLAGS_ACK,
+        };
+        rfm.send_rh(header, &[ACK])
+    }
+}
@@ -0,0 +1,170 @@
+//! Packet framing of the `RH_RF69` driver of the Arduino
+//! [RadioHead](https://www.airspayce.com/mikem/arduino/RadioHead/) library. RadioHead packets
+//! are variable length packets with a 4 byte header `to, from, id, flags` after the length byte.
+//! The radio has to be configured with [`radio_head_defaults`](crate::radio_head_defaults) to
+//! use the same modem configuration, sync words and preamble.
+
+use embedded_hal::blocking::delay::DelayMs;
+use embedded_hal::digital::v2::OutputPin;
+
+use crate::cs::ResetPin;
+use crate::error::{Error, FrameError, Result};
+use crate::rw::ReadWrite;
+use crate::Rfm69;
+
+/// Length of the header after the length byte.
+pub const HEADER_LEN: usize = 4;
+/// Maximal payload length, limited by the FIFO and AES encryption.
+pub const MAX_MESSAGE_LEN: usize = 60;
+/// Destination address which is accepted by all nodes.
+pub const BROADCAST_ADDRESS: u8 = 0xff;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Header {
+    pub to: u8,
+    pub from: u8,
+    pub id: u8,
+    pub flags: u8,
+}
+
+/// Frame with a payload borrowed from the receive or transmit buffer.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Frame<'a> {
+    pub header: Header,
+    pub payload: &'a [u8],
+}
+
+impl<'a> Frame<'a> {
+    /// Decodes a frame from a buffer starting with the length byte.
+    pub fn decode(buffer: &'a [u8]) -> core::result::Result<Self, FrameError> {
+        let len = usize::from(*buffer.first().ok_or(FrameError::Truncated)?);
+        if len < HEADER_LEN {
+            return Err(FrameError::InvalidLength);
+        } else if len > HEADER_LEN + MAX_MESSAGE_LEN {
+            return Err(FrameError::PayloadTooLarge);
+        } else if buffer.len() <= len {
+            return Err(FrameError::Truncated);
+        }
+        Ok(Frame {
+            header: Header {
+                to: buffer[1],
+                from: buffer[2],
+                id: buffer[3],
+                flags: buffer[4],
+            },
+            payload: &buffer[1 + HEADER_LEN..=len],
+        })
+    }
+
+    /// Encodes the frame including the length byte and returns the number of bytes written.
+    pub fn encode(&self, buffer: &mut [u8]) -> core::result::Result<usize, FrameError> {
+        let len = HEADER_LEN + self.payload.len();
+        if self.payload.len() > MAX_MESSAGE_LEN {
+            return Err(FrameError::PayloadTooLarge);
+        } else if buffer.len() <= len {
+            return Err(FrameError::BufferTooSmall);
+        }
+        let header = &self.header;
+        buffer[..=HEADER_LEN].copy_from_slice(&[
+            len as u8,
+            header.to,
+            header.from,
+            header.id,
+            header.flags,
+        ]);
+        buffer[1 + HEADER_LEN..=len].copy_from_slice(self.payload);
+        Ok(len + 1)
+    }
+}
+
+/// Header based address filtering of received frames.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Filter {
+    /// Accepts frames sent to the address or to [`BROADCAST_ADDRESS`]
+    Address(u8),
+    /// Accepts all frames
+    Promiscuous,
+}
+
+impl Filter {
+    pub fn accepts(&self, header: &Header) -> bool {
+        match self {
+            Filter::Address(address) => header.to == *address || header.to == BROADCAST_ADDRESS,
+            Filter::Promiscuous => true,
+        }
+    }
+}
+
+impl<T, S, D, R, Ecs, Espi> Rfm69<T, S, D, R>
+where
+    T: OutputPin<Error = Ecs>,
+    S: ReadWrite<Error = Espi>,
+    D: DelayMs<u8>,
+    R: ResetPin,
+{
+    /// Sends a RadioHead frame with [`send`](Self::send).
+    /// Returns `Frame` if the payload is longer than [`MAX_MESSAGE_LEN`].
+    pub fn send_rh(&mut self, header: Header, payload: &[u8]) -> Result<(), Ecs, Espi> {
+        let mut buffer = [0u8; 1 + HEADER_LEN + MAX_MESSAGE_LEN];
+        let len = Frame { header, payload }.encode(&mut buffer)?;
+        self.send(&buffer[..len])
+    }
+
+    /// Receives RadioHead frames with [`recv`](Self::recv) until a frame passes the filter.
+    /// Malformed frames are dropped. The buffer should be able to hold the longest frame of
+    /// `1 + HEADER_LEN + MAX_MESSAGE_LEN` bytes.
+    /// Returns `BufferTooSmall` if the buffer cannot hold the length byte and the header.
+    pub fn recv_rh<'b>(
+        &mut self,
+        filter: Filter,
+        buffer: &'b mut [u8],
+    ) -> Result<Frame<'b>, Ecs, Espi> {
+        if buffer.len() <= HEADER_LEN {
+            return Err(Error::BufferTooSmall);
+        }
+        loop {
+            self.recv(buffer)?;
+            if let Ok(frame) = Frame::decode(buffer) {
+                if filter.accepts(&frame.header) {
+                    break;
+                }
+            }
+        }
+        Ok(Frame::decode(buffer)?)
+    }
+
+    /// Receives RadioHead frames like [`recv_rh`](Self::recv_rh), waiting at most `timeout`
+    /// milliseconds for a frame that passes the filter.
+    /// Returns `RxTimeout` when no such frame was received before the timeout expired.
+    pub fn recv_rh_timeout<'b>(
+        &mut self,
+        filter: Filter,
+        buffer: &'b mut [u8],
+        timeout: u32,
+    ) -> Result<Frame<'b>, Ecs, Espi> {
+        self.recv_rh_deadline(filter, buffer, timeout)?;
+        Ok(Frame::decode(buffer)?)
+    }
+
+    /// Receives a frame that passes the filter into the buffer and returns the number of
+    /// milliseconds it took.
+    pub(crate) fn recv_rh_deadline(
+        &mut self,
+        filter: Filter,
+        buffer: &mut [u8],
+        timeout: u32,
+    ) -> Result<u32, Ecs, Espi> {
+        if buffer.len() <= HEADER_LEN {
+            return Err(Error::BufferTooSmall);
+        }
+        let mut elapsed = 0;
+        loop {
+            elapsed += self.recv_deadline(buffer, timeout.saturating_sub(elapsed))?;
+            if let Ok(frame) = Frame::decode(buffer) {
+                if filter.accepts(&frame.header) {
+                    return Ok(elapsed);
+                }
+            }
+        }
+    }
+}
@@ -1,9 +1,11 @@
 #![allow(clippy::unusual_byte_groupings)]
 
+use std::collections::VecDeque;
 use std::prelude::v1::*;
 
 use embedded_hal::blocking::delay::DelayMs;
 use embedded_hal::blocking::spi::{Operation, Transactional, Transfer, Write};
+use embedded_hal::digital::v2::OutputPin;
 
 use crate::registers::*;
 use crate::*;
@@ -11,6 +13,26 @@ use crate::*;
 struct SpiMock {
     rx_buffer: Vec<u8>,
     tx_buffer: Vec<u8>,
+    /// Packets returned by FIFO reads, a packet is removed once it is read to the end or the
+    /// FIFO is reset while it is read. Reads return `tx_buffer` when it is empty.
+    fifo: VecDeque<Vec<u8>>,
+    fifo_reading: bool,
+    /// Data written to the FIFO
+    sent: Vec<Vec<u8>>,
+    command: Option<u8>,
+}
+
+impl SpiMock {
+    fn new(rx_buffer: Vec<u8>, tx_buffer: Vec<u8>) -> Self {
+        SpiMock {
+            rx_buffer,
+            tx_buffer,
+            fifo: VecDeque::new(),
+            fifo_reading: false,
+            sent: Vec::new(),
+            command: None,
+        }
+    }
 }
 
 impl Transfer<u8> for SpiMock {
@@ -18,6 +40,19 @@ impl Transfer<u8> for SpiMock {
 
     fn transfer<'w>(&mut self, words: &'w mut [u8]) -> std::result::Result<&'w [u8], Self::Error> {
         self.rx_buffer.extend_from_slice(words);
+        if self.command.take() == Some(Registers::Fifo.read()) {
+            if let Some(packet) = self.fifo.front_mut() {
+                let len = words.len().min(packet.len());
+                words.iter_mut().for_each(|val| *val = 0);
+                words[..len].copy_from_slice(&packet[..len]);
+                packet.drain(..len);
+                self.fifo_reading = !packet.is_empty();
+                if !self.fifo_reading {
+                    self.fifo.pop_front();
+                }
+                return Ok(words);
+            }
+        }
         for (index, val) in words.iter_mut().enumerate() {
             *val = self.tx_buffer[index];
         }
@@ -30,6 +65,17 @@ impl Write<u8> for SpiMock {
 
     fn write(&mut self, words: &[u8]) -> std::result::Result<(), Self::Error> {
         self.rx_buffer.extend_from_slice(words);
+        match self.command.take() {
+            None => self.command = words.first().copied(),
+            Some(command) if command == Registers::Fifo.write() => self.sent.push(words.to_vec()),
+            Some(command) if command == Registers::IrqFlags2.write() && words[0] & 0x10 != 0 => {
+                if self.fifo_reading {
+                    self.fifo.pop_front();
+                    self.fifo_reading = false;
+                }
+            }
+            Some(_) => {}
+        }
         Ok(())
     }
 }
@@ -59,15 +105,35 @@ impl DelayMs<u8> for DelayMock {
     fn delay_ms(&mut self, _: u8) {}
 }
 
+struct PinMock {
+    states: Vec<bool>,
+    fail: bool,
+}
+
+impl OutputPin for PinMock {
+    type Error = &'static str;
+
+    fn set_low(&mut self) -> std::result::Result<(), Self::Error> {
+        self.set_state(false)
+    }
+
+    fn set_high(&mut self) -> std::result::Result<(), Self::Error> {
+        self.set_state(true)
+    }
+}
+
+impl PinMock {
+    fn set_state(&mut self, state: bool) -> std::result::Result<(), &'static str> {
+        if self.fail {
+            return Err("pin");
+        }
+        self.states.push(state);
+        Ok(())
+    }
+}
+
 fn setup_rfm(rx_buffer: Vec<u8>, tx_buffer: Vec<u8>) -> Rfm69<NoCs, SpiMock, DelayMock> {
-    Rfm69::new(
-        SpiMock {
-            rx_buffer,
-            tx_buffer,
-        },
-        NoCs,
-        DelayMock,
-    )
+    Rfm69::new(SpiMock::new(rx_buffer, tx_buffer), NoCs, DelayMock)
 }
 
 #[test]
@@ -81,13 +147,7 @@ fn test_read_all_regs() {
 
 #[test]
 fn test_read_all_regs_transactional() {
-    let mut rfm = Rfm69::new_without_cs(
-        SpiMock {
-            rx_buffer: Vec::new(),
-            tx_buffer: (1..=0x4f).collect(),
-        },
-        DelayMock,
-    );
+    let mut rfm = Rfm69::new_without_cs(SpiMock::new(Vec::new(), (1..=0x4f).collect()), DelayMock);
 
     let result = rfm.read_all_regs().unwrap_or([0; 0x4f]);
     assert_eq!(rfm.spi.0.rx_buffer[0], Registers::OpMode.read());
@@ -525,3 +585,1575 @@ fn test_wait_packet_sent() {
     rfm.wait_packet_sent().ok().unwrap();
     assert_eq!(rfm.spi.rx_buffer[0], Registers::IrqFlags2.read());
 }
+
+#[test]
+fn test_output_power() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0, 0]);
+
+    rfm.output_power(13).ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=1],
+        [Registers::PaLevel.write(), 0b100_11111]
+    );
+    assert_eq!(
+        rfm.spi.rx_buffer[2..=5],
+        [
+            Registers::TestPa1.write(),
+            0x55,
+            Registers::TestPa2.write(),
+            0x70
+        ]
+    );
+    rfm.output_power(14).err().unwrap();
+
+    rfm.module_variant(ModuleVariant::HighPower);
+    rfm.spi.rx_buffer.clear();
+    rfm.output_power(-2).ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=1],
+        [Registers::PaLevel.write(), 0b010_10000]
+    );
+
+    rfm.spi.rx_buffer.clear();
+    rfm.output_power(17).ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=1],
+        [Registers::PaLevel.write(), 0b011_11111]
+    );
+
+    rfm.spi.rx_buffer.clear();
+    rfm.output_power(20).ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=5],
+        [
+            Registers::PaLevel.write(),
+            0b011_11111,
+            Registers::TestPa1.write(),
+            0x55,
+            Registers::TestPa2.write(),
+            0x70
+        ]
+    );
+    rfm.output_power(21).err().unwrap();
+
+    rfm.ocp(OcpConfig {
+        enabled: false,
+        trim: 95,
+    })
+    .ok()
+    .unwrap();
+    rfm.spi.rx_buffer.clear();
+    rfm.mode(Mode::Transmitter).ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=3],
+        [
+            Registers::TestPa1.write(),
+            0x5d,
+            Registers::TestPa2.write(),
+            0x7c
+        ]
+    );
+
+    rfm.spi.rx_buffer.clear();
+    rfm.mode(Mode::Receiver).ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=3],
+        [
+            Registers::TestPa1.write(),
+            0x55,
+            Registers::TestPa2.write(),
+            0x70
+        ]
+    );
+}
+
+#[test]
+fn test_pa_ramp() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0b0000_1001, 0]);
+
+    rfm.pa_ramp(PaRamp::Us500).ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=1],
+        [Registers::PaRamp.write(), 0b0000_0011]
+    );
+
+    rfm.spi.rx_buffer.clear();
+    assert!(matches!(rfm.read_pa_ramp().ok().unwrap(), PaRamp::Us40));
+    assert_eq!(rfm.spi.rx_buffer[0], Registers::PaRamp.read());
+}
+
+#[test]
+fn test_ocp() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0b000_0_1010, 0]);
+
+    rfm.ocp(OcpConfig {
+        enabled: true,
+        trim: 120,
+    })
+    .ok()
+    .unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=1],
+        [Registers::Ocp.write(), 0b000_1_1111]
+    );
+    rfm.ocp(OcpConfig {
+        enabled: true,
+        trim: 40,
+    })
+    .err()
+    .unwrap();
+    assert!(matches!(
+        rfm.ocp(OcpConfig {
+            enabled: true,
+            trim: 97,
+        })
+        .err()
+        .unwrap(),
+        Error::OcpOutOfRange
+    ));
+
+    rfm.spi.rx_buffer.clear();
+    let ocp = rfm.read_ocp().ok().unwrap();
+    assert_eq!(rfm.spi.rx_buffer[0], Registers::Ocp.read());
+    assert!(!ocp.enabled);
+    assert_eq!(ocp.trim, 95);
+
+    rfm.ocp(OcpConfig {
+        enabled: true,
+        trim: 95,
+    })
+    .ok()
+    .unwrap();
+    rfm.module_variant(ModuleVariant::HighPower);
+    rfm.output_power(17).ok().unwrap();
+    rfm.mode(Mode::Transmitter).ok().unwrap();
+    rfm.output_power(20).ok().unwrap();
+    assert!(matches!(
+        rfm.mode(Mode::Transmitter).err().unwrap(),
+        Error::OcpLimit
+    ));
+}
+
+#[test]
+fn test_listen() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0b1_00_000_00, 0]);
+
+    rfm.enter_listen(ListenConfig {
+        idle_duration: 1_000_000,
+        rx_duration: 1_000,
+        criteria: ListenCriteria::RssiThreshold,
+        end: ListenEnd::ResumeListen,
+    })
+    .ok()
+    .unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[9..=16],
+        [
+            Registers::Listen1.write(),
+            0b10_01_0_10_0,
+            244,
+            16,
+            Registers::OpMode.read(),
+            0,
+            Registers::OpMode.write(),
+            0b1_10_001_00
+        ]
+    );
+
+    rfm.spi.rx_buffer.clear();
+    rfm.exit_listen().ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=5],
+        [
+            Registers::OpMode.read(),
+            0,
+            Registers::OpMode.write(),
+            0b1_01_001_00,
+            Registers::OpMode.write(),
+            0b1_00_001_00
+        ]
+    );
+
+    rfm.enter_listen(ListenConfig {
+        idle_duration: 70_000_000,
+        rx_duration: 1_000,
+        criteria: ListenCriteria::RssiThreshold,
+        end: ListenEnd::ResumeListen,
+    })
+    .err()
+    .unwrap();
+}
+
+#[test]
+fn test_auto_modes() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0, 0]);
+
+    rfm.auto_modes(AutoModes {
+        enter: EnterCondition::FifoLevel,
+        exit: ExitCondition::PacketSent,
+        intermediate: IntermediateMode::Transmitter,
+    })
+    .ok()
+    .unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=1],
+        [Registers::AutoModes.write(), 0b010_110_11]
+    );
+
+    rfm.spi.rx_buffer.clear();
+    rfm.clear_auto_modes().ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=1],
+        [Registers::AutoModes.write(), 0b000_000_00]
+    );
+}
+
+#[test]
+fn test_send() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0, 0b0000_1_000]);
+
+    rfm.send(&[0x12, 0x34]).ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer,
+        [
+            Registers::IrqFlags2.write(),
+            0x10,
+            Registers::AutoModes.write(),
+            0b001_110_11,
+            Registers::Fifo.write(),
+            0x12,
+            0x34,
+            Registers::IrqFlags1.read(),
+            0,
+            0,
+            Registers::AutoModes.write(),
+            0b000_000_00
+        ]
+    );
+
+    rfm.spi.tx_buffer = vec![0b000000_1_0, 0];
+    rfm.send(&[0x12, 0x34]).err().unwrap();
+
+    // The sequencer has not started yet.
+    rfm.spi.tx_buffer = vec![0, 0b0_1_000000];
+    rfm.send(&[0x12, 0x34]).err().unwrap();
+
+    // The packet was sent before the first poll.
+    rfm.spi.tx_buffer = vec![0, 0];
+    rfm.send(&[0x12, 0x34]).ok().unwrap();
+}
+
+#[test]
+fn test_recv_auto() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0b1000_0_1_00, 0]);
+    rfm.spi.fifo.push_back(vec![0x12, 0x34]);
+
+    let mut buffer = [0u8; 2];
+    rfm.recv_auto(&mut buffer).ok().unwrap();
+    assert_eq!(buffer, [0x12, 0x34]);
+    assert_eq!(rfm.rssi(), -66.0);
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=1],
+        [Registers::AutoModes.write(), 0b100_001_01]
+    );
+    let len = rfm.spi.rx_buffer.len();
+    assert_eq!(
+        rfm.spi.rx_buffer[len - 2..],
+        [Registers::AutoModes.write(), 0b000_000_00]
+    );
+}
+
+#[test]
+fn test_recv_timeout() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0b1_0000000, 0b00000_1_00, 0, 0]);
+
+    let mut buffer = [0u8; 2];
+    rfm.recv_timeout(&mut buffer, 100).ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=7],
+        [
+            Registers::BitrateMsb.read(),
+            0,
+            0,
+            Registers::PayloadLength.read(),
+            0,
+            Registers::RxTimeout1.write(),
+            6,
+            144
+        ]
+    );
+    assert_eq!(buffer, [0b1_0000000, 0b00000_1_00]);
+
+    rfm.spi.tx_buffer[1] = 0;
+    rfm.spi.rx_buffer.clear();
+    assert!(matches!(
+        rfm.recv_timeout(&mut buffer, 3).err().unwrap(),
+        Error::RxTimeout
+    ));
+    let len = rfm.spi.rx_buffer.len();
+    assert_eq!(
+        rfm.spi.rx_buffer[len - 3..],
+        [Registers::RxTimeout1.write(), 0, 0]
+    );
+}
+
+#[test]
+fn test_read_packet() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0b0_00_0_0_10_0, 3, 0xff, 0xfe]);
+
+    let mut buffer = [0u8; 4];
+    let packet = rfm.read_packet(&mut buffer).ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=6],
+        [
+            Registers::PacketConfig1.read(),
+            0,
+            0,
+            Registers::Fifo.read(),
+            0,
+            Registers::RssiValue.read(),
+            0
+        ]
+    );
+    assert_eq!(packet.len, 2);
+    assert_eq!(packet.address, Some(0b0_00_0_0_10_0));
+    assert_eq!(packet.rssi, -2.0);
+    assert!((packet.afc - 62_683.1).abs() < 0.1);
+    assert!((packet.fei + 122.1).abs() < 0.1);
+    assert_eq!(buffer[0..2], [0b0_00_0_0_10_0, 3]);
+
+    rfm.spi.tx_buffer[1] = 6;
+    rfm.read_packet(&mut buffer).err().unwrap();
+}
+
+#[test]
+fn test_recv_packet() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0b1_00_0_0_10_0, 0, 0, 0]);
+    rfm.spi.fifo.push_back(vec![3, 0x05, 0x12, 0x34]);
+
+    let mut buffer = [0u8; 2];
+    let packet = rfm.recv_packet(&mut buffer).ok().unwrap();
+    assert_eq!(packet.len, 2);
+    assert_eq!(packet.address, Some(0x05));
+    assert_eq!(packet.rssi, -66.0);
+    assert_eq!(buffer, [0x12, 0x34]);
+    assert!(rfm.spi.fifo.is_empty());
+
+    rfm.spi.fifo.push_back(vec![5, 0x05, 1, 2, 3, 4]);
+    rfm.spi.fifo.push_back(vec![3, 0x06, 0x56, 0x78]);
+    rfm.spi.rx_buffer.clear();
+    assert!(matches!(
+        rfm.recv_packet(&mut buffer).err().unwrap(),
+        Error::BufferTooSmall
+    ));
+    let len = rfm.spi.rx_buffer.len();
+    assert_eq!(
+        rfm.spi.rx_buffer[len - 2..],
+        [Registers::IrqFlags2.write(), 0x10]
+    );
+
+    let packet = rfm.recv_packet(&mut buffer).ok().unwrap();
+    assert_eq!(packet.address, Some(0x06));
+    assert_eq!(buffer, [0x56, 0x78]);
+}
+
+#[test]
+fn test_afc() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0b0_1_0_1_1_1_0_0, 0xff]);
+
+    rfm.afc(AfcConfig {
+        auto_on: true,
+        auto_clear: false,
+    })
+    .ok()
+    .unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=1],
+        [Registers::AfcFei.write(), 0b0000_0_1_0_0]
+    );
+
+    rfm.spi.rx_buffer.clear();
+    rfm.afc_mode(AfcMode::ImprovedLowBeta).ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=1],
+        [Registers::AfcCtrl.write(), 0b00_1_00000]
+    );
+
+    rfm.spi.rx_buffer.clear();
+    let afc = rfm.measure_afc().ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=7],
+        [
+            Registers::AfcFei.read(),
+            0,
+            Registers::AfcFei.write(),
+            0b0_0_0_0_1_1_0_1,
+            Registers::AfcFei.read(),
+            0,
+            Registers::AfcMsb.read(),
+            0
+        ]
+    );
+    assert!((afc - 1_453_064.0).abs() < 1.0);
+
+    rfm.spi.rx_buffer.clear();
+    rfm.measure_fei().ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=7],
+        [
+            Registers::AfcFei.read(),
+            0,
+            Registers::AfcFei.write(),
+            0b0_0_1_0_1_1_0_0,
+            Registers::AfcFei.read(),
+            0,
+            Registers::FeiMsb.read(),
+            0
+        ]
+    );
+
+    rfm.spi.tx_buffer = vec![0xff, 0xff];
+    assert!((rfm.fei_value().ok().unwrap() + 61.0).abs() < 0.1);
+}
+
+#[test]
+fn test_measure_rssi() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0b1000_0_0_1_0, 0]);
+
+    assert_eq!(rfm.measure_rssi().ok().unwrap(), -65.0);
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=5],
+        [
+            Registers::RssiConfig.write(),
+            0b000000_0_1,
+            Registers::RssiConfig.read(),
+            0,
+            Registers::RssiValue.read(),
+            0
+        ]
+    );
+
+    rfm.spi.rx_buffer.clear();
+    let floor = rfm.noise_floor(4).ok().unwrap();
+    assert_eq!(floor.mean, -65.0);
+    assert_eq!(floor.min, -65.0);
+    assert_eq!(floor.max, -65.0);
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=3],
+        [
+            Registers::OpMode.read(),
+            0,
+            Registers::OpMode.write(),
+            0b100_100_10
+        ]
+    );
+    let len = rfm.spi.rx_buffer.len();
+    assert_eq!(
+        rfm.spi.rx_buffer[len - 7..len - 3],
+        [
+            Registers::OpMode.read(),
+            0,
+            Registers::OpMode.write(),
+            0b100_001_10
+        ]
+    );
+
+    // The previous mode is restored when the measurement times out.
+    rfm.spi.tx_buffer[0] = 0b1000_0_0_0_0;
+    rfm.spi.rx_buffer.clear();
+    rfm.noise_floor(1).err().unwrap();
+    let len = rfm.spi.rx_buffer.len();
+    assert_eq!(
+        rfm.spi.rx_buffer[len - 5..len - 3],
+        [Registers::OpMode.write(), 0b100_001_00]
+    );
+}
+
+#[test]
+fn test_send_csma() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0b1000_0_0_1_0, 0]);
+
+    let csma = CsmaConfig {
+        threshold: -90.0,
+        retries: 3,
+        backoff: 10,
+        max_backoff: 100,
+    };
+    assert!(matches!(
+        rfm.send_csma(&[0x12, 0x34], &csma).err().unwrap(),
+        Error::ChannelBusy
+    ));
+    let assessments = rfm
+        .spi
+        .rx_buffer
+        .windows(2)
+        .filter(|w| w == &[Registers::RssiConfig.write(), 0b000000_0_1])
+        .count();
+    assert_eq!(assessments, 4);
+    assert!(!rfm.spi.rx_buffer.contains(&Registers::Fifo.write()));
+}
+
+#[test]
+fn test_temperature() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0b1000_0_0_00, 0]);
+
+    assert_eq!(rfm.temperature(2).ok().unwrap(), 39);
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=5],
+        [
+            Registers::Temp1.write(),
+            0b0000_1_0_00,
+            Registers::Temp1.read(),
+            0,
+            Registers::Temp2.read(),
+            0
+        ]
+    );
+
+    rfm.spi.tx_buffer[0] = 0b0000_0_1_00;
+    rfm.temperature(0).err().unwrap();
+
+    // The previous mode is restored when the measurement times out.
+    rfm.spi.tx_buffer[0] = 0b1000_0_1_00;
+    rfm.mode(Mode::Receiver).ok().unwrap();
+    rfm.spi.rx_buffer.clear();
+    rfm.temperature(0).err().unwrap();
+    let len = rfm.spi.rx_buffer.len();
+    assert_eq!(
+        rfm.spi.rx_buffer[len - 5..len - 3],
+        [Registers::OpMode.write(), 0b100_100_00]
+    );
+}
+
+#[test]
+fn test_low_bat() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0b000_1_0_010, 0, 0]);
+
+    rfm.low_bat(LowBatConfig {
+        enabled: true,
+        trim: LowBatTrim::V1dot976,
+    })
+    .ok()
+    .unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=1],
+        [Registers::LowBat.write(), 0b000_0_1_100]
+    );
+
+    rfm.spi.rx_buffer.clear();
+    assert!(rfm.low_battery().ok().unwrap());
+    assert_eq!(rfm.spi.rx_buffer[0], Registers::LowBat.read());
+
+    rfm.spi.tx_buffer[0] = 0b000_0_1_010;
+    assert!(!rfm.low_battery().ok().unwrap());
+
+    rfm.spi.rx_buffer.clear();
+    rfm.dio_mapping(DioMapping::low_bat()).ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=2],
+        [Registers::DioMapping1.write(), 0b00_00_10_00, 0b00_00_0_111]
+    );
+}
+
+#[test]
+fn test_clk_out() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0, 0]);
+
+    rfm.clk_out(ClkOut::Div4).ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=2],
+        [Registers::DioMapping1.write(), 0b00_00_00_00, 0b00_00_0_010]
+    );
+
+    rfm.spi.rx_buffer.clear();
+    rfm.dio_mapping(DioMapping {
+        pin: DioPin::Dio5,
+        dio_type: DioType::Dio11,
+        dio_mode: DioMode::Both,
+    })
+    .ok()
+    .unwrap();
+    rfm.mode(Mode::Receiver).ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[7..=9],
+        [Registers::DioMapping1.write(), 0b00_00_00_00, 0b00_11_0_010]
+    );
+}
+
+#[test]
+fn test_calibrate_rc_oscillator() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0b0_0_000000, 0]);
+
+    rfm.calibrate_rc_oscillator().err().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=3],
+        [
+            Registers::Osc1.write(),
+            0b1_0_000000,
+            Registers::Osc1.read(),
+            0
+        ]
+    );
+
+    rfm.spi.tx_buffer[0] = 0b0_1_000000;
+    rfm.calibrate_rc_oscillator().ok().unwrap();
+
+    // The previous mode is restored when the calibration times out.
+    rfm.spi.tx_buffer[0] = 0b1_0_000000;
+    rfm.mode(Mode::Receiver).ok().unwrap();
+    rfm.spi.rx_buffer.clear();
+    rfm.calibrate_rc_oscillator().err().unwrap();
+    let len = rfm.spi.rx_buffer.len();
+    assert_eq!(
+        rfm.spi.rx_buffer[len - 5..len - 3],
+        [Registers::OpMode.write(), 0b100_100_00]
+    );
+}
+
+#[test]
+fn test_ook_threshold() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0, 0]);
+
+    rfm.ook_threshold(OokThreshold::Fixed(10)).ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=3],
+        [Registers::OokPeak.write(), 0b00_000_000, 0b10_000000, 10]
+    );
+
+    rfm.spi.rx_buffer.clear();
+    rfm.ook_threshold(OokThreshold::Peak {
+        step: OokPeakStep::Db2dot0,
+        decrement: OokPeakDecrement::TwicePerChip,
+        floor: 6,
+    })
+    .ok()
+    .unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=3],
+        [Registers::OokPeak.write(), 0b01_011_100, 0b10_000000, 6]
+    );
+
+    rfm.spi.rx_buffer.clear();
+    rfm.ook_threshold(OokThreshold::Average(OokAverageFilter::ChipRateBy2Pi))
+        .ok()
+        .unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=3],
+        [Registers::OokPeak.write(), 0b10_000_000, 0b11_000000, 6]
+    );
+}
+
+#[test]
+fn test_agc() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0b0_0_100_000, 0]);
+
+    rfm.agc(AgcConfig {
+        auto_ref: true,
+        ref_level: 5,
+        snr_margin: 5,
+        steps: [16, 7, 11, 9, 11],
+    })
+    .ok()
+    .unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=4],
+        [
+            Registers::AgcRef.write(),
+            0b0_1_000101,
+            0b101_10000,
+            0b0111_1011,
+            0b1001_1011
+        ]
+    );
+
+    rfm.spi.rx_buffer.clear();
+    assert!(matches!(rfm.current_lna_gain().ok().unwrap(), LnaGain::G4));
+    assert_eq!(rfm.spi.rx_buffer[0], Registers::Lna.read());
+}
+
+#[test]
+fn test_sync_config() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0, 0]);
+
+    rfm.sync_config(SyncConfig {
+        fill_condition: FifoFillCondition::Always,
+        tolerance: 3,
+        len: 2,
+        value: [0x2d, 0xd4, 0, 0, 0, 0, 0, 0],
+    })
+    .ok()
+    .unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[0..=4],
+        [
+            Registers::SyncConfig.write(),
+            0b1_1_001_011,
+            Registers::SyncValue1.write(),
+            0x2d,
+            0xd4
+        ]
+    );
+
+    rfm.spi.rx_buffer.clear();
+    rfm.sync_config(SyncConfig {
+        fill_condition: FifoFillCondition::SyncAddress,
+        tolerance: 1,
+        len: 0,
+        value: [0; 8],
+    })
+    .ok()
+    .unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer,
+        [Registers::SyncConfig.write(), 0b0_0_000_001]
+    );
+
+    assert!(matches!(
+        rfm.sync_config(SyncConfig {
+            fill_condition: FifoFillCondition::SyncAddress,
+            tolerance: 0,
+            len: 9,
+            value: [0; 8],
+        })
+        .err()
+        .unwrap(),
+        Error::SyncSize
+    ));
+
+    rfm.spi.rx_buffer.clear();
+    assert!(matches!(
+        rfm.sync_config(SyncConfig {
+            fill_condition: FifoFillCondition::SyncAddress,
+            tolerance: 8,
+            len: 2,
+            value: [0; 8],
+        })
+        .err()
+        .unwrap(),
+        Error::SyncTolerance
+    ));
+    assert!(rfm.spi.rx_buffer.is_empty());
+}
+
+#[test]
+fn test_config() {
+    let mut regs = vec![0u8; 0x4f];
+    let mut set = |reg: Registers, values: &[u8]| {
+        let start = reg as usize - 1;
+        regs[start..start + values.len()].copy_from_slice(values);
+    };
+    set(Registers::DataModul, &[0b0_00_01_0_01]);
+    set(Registers::BitrateMsb, &[0x0d, 0x05, 0x01, 0x3b]);
+    set(Registers::FrfMsb, &[0xe4, 0xc0, 0x00]);
+    set(Registers::PaLevel, &[0b011_11111, 0x09, 0x1a]);
+    set(Registers::Lna, &[0x88, 0x55, 0x8b]);
+    set(Registers::RssiThresh, &[0xe4]);
+    set(
+        Registers::PreambleMsb,
+        &[0x00, 0x04, 0b1_1_001_010, 0x2d, 0xd4],
+    );
+    set(
+        Registers::PacketConfig1,
+        &[0b1_10_1_0_10_0, 0x40, 0x12, 0xff],
+    );
+    set(Registers::FifoThresh, &[0x8a, 0x13]);
+    let mut rfm = setup_rfm(Vec::new(), regs);
+
+    let config = rfm.config().ok().unwrap();
+    assert_eq!(rfm.spi.rx_buffer[0], Registers::OpMode.read());
+    assert_eq!(config.modulation.data_mode, DataMode::Packet);
+    assert_eq!(config.modulation.modulation_type, ModulationType::Ook);
+    assert_eq!(config.modulation.shaping, ModulationShaping::Shaping01);
+    assert!((config.bit_rate - 9_600.96).abs() < 0.01);
+    assert!((config.fdev - 19_226.0).abs() < 1.0);
+    assert_eq!(config.frequency, 915_000_000.0);
+    assert_eq!(
+        config.pa_level,
+        PaLevel {
+            pa0: false,
+            pa1: true,
+            pa2: true,
+            output_power: 31,
+        }
+    );
+    assert_eq!(config.pa_ramp, PaRamp::Us40);
+    assert_eq!(
+        config.ocp,
+        OcpConfig {
+            enabled: true,
+            trim: 95,
+        }
+    );
+    assert_eq!(config.lna.zin, LnaImpedance::Ohm200);
+    assert_eq!(config.lna.gain_select, LnaGain::AgcLoop);
+    assert_eq!(
+        config.rx_bw,
+        RxBwConfig::Ook(RxBw {
+            dcc_cutoff: DccCutoff::Percent4,
+            rx_bw: RxBwOok::Khz5dot2,
+        })
+    );
+    assert_eq!(
+        config.afc_bw,
+        RxBwConfig::Ook(RxBw {
+            dcc_cutoff: DccCutoff::Percent1,
+            rx_bw: RxBwOok::Khz25dot0,
+        })
+    );
+    assert_eq!(config.rssi_threshold, 0xe4);
+    assert_eq!(config.preamble, 4);
+    assert_eq!(config.sync.fill_condition, FifoFillCondition::Always);
+    assert_eq!(config.sync.tolerance, 2);
+    assert_eq!(config.sync.len, 2);
+    assert_eq!(config.sync.value[..2], [0x2d, 0xd4]);
+    assert_eq!(config.packet.format, PacketFormat::Variable(0x40));
+    assert_eq!(config.packet.dc, PacketDc::Whitening);
+    assert!(config.packet.crc);
+    assert_eq!(config.packet.filtering, PacketFiltering::Broadcast);
+    assert_eq!(config.node_address, 0x12);
+    assert_eq!(config.broadcast_address, 0xff);
+    assert_eq!(config.fifo_mode, FifoMode::NotEmpty);
+    assert_eq!(config.fifo_threshold, 0x0a);
+    assert_eq!(
+        config.packet.interpacket_rx_delay,
+        InterPacketRxDelay::Delay2Bits
+    );
+    assert!(config.packet.auto_rx_restart);
+    assert!(config.aes);
+}
+
+#[test]
+fn test_apply() {
+    let mut rfm = setup_rfm(Vec::new(), Vec::new());
+    let config = RadioConfig {
+        modulation: Modulation {
+            data_mode: DataMode::Packet,
+            modulation_type: ModulationType::Fsk,
+            shaping: ModulationShaping::Shaping01,
+        },
+        bit_rate: 9_600.0,
+        fdev: 19_200.0,
+        frequency: 915_000_000.0,
+        pa_level: PaLevel {
+            pa0: false,
+            pa1: true,
+            pa2: true,
+            output_power: 31,
+        },
+        pa_ramp: PaRamp::Us40,
+        ocp: OcpConfig {
+            enabled: false,
+            trim: 95,
+        },
+        lna: LnaConfig {
+            zin: LnaImpedance::Ohm200,
+            gain_select: LnaGain::AgcLoop,
+        },
+        rx_bw: RxBwConfig::Fsk(RxBw {
+            dcc_cutoff: DccCutoff::Percent4,
+            rx_bw: RxBwFsk::Khz10dot4,
+        }),
+        afc_bw: RxBwConfig::Fsk(RxBw {
+            dcc_cutoff: DccCutoff::Percent1,
+            rx_bw: RxBwFsk::Khz50dot0,
+        }),
+        rssi_threshold: 0xe4,
+        preamble: 4,
+        sync: SyncConfig {
+            fill_condition: FifoFillCondition::SyncAddress,
+            tolerance: 1,
+            len: 2,
+            value: [0x2d, 0xd4, 0, 0, 0, 0, 0, 0],
+        },
+        packet: PacketConfig {
+            format: PacketFormat::Variable(0x40),
+            dc: PacketDc::Whitening,
+            crc: true,
+            filtering: PacketFiltering::Broadcast,
+            interpacket_rx_delay: InterPacketRxDelay::Delay2Bits,
+            auto_rx_restart: true,
+        },
+        node_address: 0x12,
+        broadcast_address: 0xff,
+        fifo_mode: FifoMode::NotEmpty,
+        fifo_threshold: 0x0a,
+        aes: true,
+    };
+
+    rfm.apply(&config).ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer,
+        [
+            Registers::DataModul.write(),
+            0b0_00_00_0_01,
+            0x0d,
+            0x05,
+            0x01,
+            0x3a,
+            0xe4,
+            0xc0,
+            0x00,
+            Registers::PaLevel.write(),
+            0b011_11111,
+            0x09,
+            0x0a,
+            Registers::Lna.write(),
+            0x80,
+            0x55,
+            0x8b,
+            Registers::RssiThresh.write(),
+            0xe4,
+            0x00,
+            0x00,
+            0x00,
+            0x04,
+            0b1_0_001_001,
+            0x2d,
+            0xd4,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            Registers::PacketConfig1.write(),
+            0b1_10_1_0_10_0,
+            0x40,
+            0x12,
+            0xff,
+            0x00,
+            0x8a,
+            0b0001_0_0_1_1,
+        ]
+    );
+
+    let mut invalid = config;
+    invalid.sync.len = 9;
+    assert!(matches!(
+        rfm.apply(&invalid).err().unwrap(),
+        Error::SyncSize
+    ));
+    invalid = config;
+    invalid.ocp.trim = 130;
+    assert!(matches!(
+        rfm.apply(&invalid).err().unwrap(),
+        Error::OcpOutOfRange
+    ));
+}
+
+#[test]
+fn test_cache() {
+    let mut regs = vec![0u8; 0x4f];
+    regs[0] = 0b000_001_00;
+    regs[Registers::DioMapping2 as usize - 1] = 0x07;
+    let mut rfm = setup_rfm(Vec::new(), regs);
+
+    rfm.enable_cache().ok().unwrap();
+    assert_eq!(rfm.spi.rx_buffer[0], Registers::OpMode.read());
+    assert_eq!(
+        rfm.spi.rx_buffer[0x50..]
+            .iter()
+            .step_by(2)
+            .copied()
+            .collect::<Vec<_>>(),
+        [
+            Registers::TestLna.read(),
+            Registers::TestPa1.read(),
+            Registers::TestPa2.read(),
+            Registers::TestDagc.read()
+        ]
+    );
+
+    rfm.spi.rx_buffer.clear();
+    rfm.spi.tx_buffer[0] = 0xff;
+    rfm.fifo_mode(FifoMode::NotEmpty).ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer,
+        [Registers::FifoThresh.write(), 0b1_0000000]
+    );
+
+    rfm.spi.rx_buffer.clear();
+    rfm.mode(Mode::Receiver).ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer,
+        [
+            Registers::OpMode.read(),
+            0,
+            Registers::OpMode.write(),
+            0b111_100_11
+        ]
+    );
+
+    rfm.spi.rx_buffer.clear();
+    assert!(rfm.is_packet_ready().ok().unwrap());
+    assert_eq!(rfm.spi.rx_buffer, [Registers::IrqFlags2.read(), 0]);
+
+    rfm.spi.tx_buffer[Registers::FifoThresh as usize - 1] = 0x42;
+    rfm.resync().ok().unwrap();
+    rfm.spi.rx_buffer.clear();
+    assert_eq!(rfm.read(Registers::FifoThresh).ok().unwrap(), 0x42);
+    assert!(rfm.spi.rx_buffer.is_empty());
+
+    rfm.disable_cache();
+    rfm.spi.rx_buffer.clear();
+    rfm.read(Registers::OpMode).ok().unwrap();
+    assert_eq!(rfm.spi.rx_buffer, [Registers::OpMode.read(), 0]);
+}
+
+#[test]
+fn test_reset_init() {
+    let mut rfm = Rfm69::new_with_reset(
+        SpiMock::new(Vec::new(), vec![0x24]),
+        NoCs,
+        DelayMock,
+        PinMock {
+            states: Vec::new(),
+            fail: false,
+        },
+    );
+
+    rfm.init().ok().unwrap();
+    assert_eq!(rfm.reset.states, [true, false]);
+    assert_eq!(rfm.spi.rx_buffer, [Registers::Version.read(), 0]);
+
+    rfm.spi.tx_buffer[0] = 0xff;
+    assert!(matches!(
+        rfm.init().err().unwrap(),
+        Error::UnknownChip(0xff)
+    ));
+    assert_eq!(rfm.reset.states, [true, false, true, false]);
+
+    rfm.reset.fail = true;
+    assert!(matches!(rfm.init().err().unwrap(), Error::Reset));
+
+    // Without a reset pin the chip and the driver keep their state.
+    let mut rfm = setup_rfm(Vec::new(), vec![0x24, 0]);
+    rfm.clk_out(ClkOut::Rc).ok().unwrap();
+    rfm.spi.rx_buffer.clear();
+    rfm.init().ok().unwrap();
+    assert_eq!(rfm.spi.rx_buffer, [Registers::Version.read(), 0]);
+    rfm.spi.rx_buffer.clear();
+    rfm.mode(Mode::Receiver).ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[4..=6],
+        [Registers::DioMapping1.write(), 0, ClkOut::Rc as u8]
+    );
+}
+
+#[test]
+fn test_recommended_defaults() {
+    let rfm = setup_rfm(Vec::new(), vec![0b0_0_001_000]);
+
+    let rfm = recommended_defaults(rfm).ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer,
+        [
+            Registers::Lna.read(),
+            0,
+            Registers::Lna.write(),
+            0x88,
+            Registers::RxBw.write(),
+            0x55,
+            Registers::AfcBw.write(),
+            0x8b,
+            Registers::DioMapping1.write(),
+            0x00,
+            0x07,
+            Registers::RssiThresh.write(),
+            0xe4,
+            Registers::SyncValue1.write(),
+            0x01,
+            0x01,
+            0x01,
+            0x01,
+            0x01,
+            0x01,
+            0x01,
+            0x01,
+            Registers::FifoThresh.write(),
+            0x8f,
+            Registers::TestDagc.write(),
+            0x30
+        ]
+    );
+}
+
+#[test]
+fn test_radio_head_defaults() {
+    let rfm = setup_rfm(Vec::new(), vec![0, 0]);
+
+    let rfm = radio_head_defaults(rfm, ModemConfigChoice::GfskRb2Fd5, 915_000_000.0)
+        .ok()
+        .unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[7..],
+        [
+            Registers::FifoThresh.write(),
+            0x8f,
+            Registers::TestDagc.write(),
+            0x30,
+            Registers::DataModul.write(),
+            0x01,
+            0x3e,
+            0x80,
+            0x00,
+            0x52,
+            Registers::RxBw.write(),
+            0xf4,
+            0xf5,
+            Registers::PacketConfig1.write(),
+            0xd0,
+            Registers::SyncConfig.write(),
+            0b1_0_001_000,
+            Registers::SyncValue1.write(),
+            0x2d,
+            0xd4,
+            Registers::PreambleMsb.write(),
+            0x00,
+            0x04,
+            Registers::FrfMsb.write(),
+            0xe4,
+            0xc0,
+            0x00,
+            Registers::PacketConfig2.read(),
+            0,
+            Registers::PacketConfig2.write(),
+            0x00
+        ]
+    );
+
+    let rfm = setup_rfm(Vec::new(), vec![0, 0]);
+    let rfm = radio_head_defaults(rfm, ModemConfigChoice::OokRb32Bw64, 433_000_000.0)
+        .ok()
+        .unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[11..=20],
+        [
+            Registers::DataModul.write(),
+            0x08,
+            0x03,
+            0xe8,
+            0x00,
+            0x10,
+            Registers::RxBw.write(),
+            0xe2,
+            0xe2,
+            Registers::PacketConfig1.write()
+        ]
+    );
+}
+
+#[test]
+fn test_rh_frame() {
+    let header = rh_rf69::Header {
+        to: 0x02,
+        from: 0x01,
+        id: 0x05,
+        flags: 0x40,
+    };
+    let frame = rh_rf69::Frame {
+        header,
+        payload: b"Hi",
+    };
+
+    let mut buffer = [0u8; 8];
+    assert_eq!(frame.encode(&mut buffer), Ok(7));
+    assert_eq!(buffer[..7], [6, 0x02, 0x01, 0x05, 0x40, b'H', b'i']);
+    assert_eq!(rh_rf69::Frame::decode(&buffer), Ok(frame));
+
+    assert_eq!(
+        frame.encode(&mut buffer[..6]),
+        Err(FrameError::BufferTooSmall)
+    );
+    assert_eq!(
+        rh_rf69::Frame::decode(&buffer[..6]),
+        Err(FrameError::Truncated)
+    );
+    assert_eq!(rh_rf69::Frame::decode(&[]), Err(FrameError::Truncated));
+    assert_eq!(
+        rh_rf69::Frame::decode(&[3, 0, 0, 0]),
+        Err(FrameError::InvalidLength)
+    );
+    assert_eq!(
+        rh_rf69::Frame::decode(&[65, 0, 0, 0, 0]),
+        Err(FrameError::PayloadTooLarge)
+    );
+
+    assert!(rh_rf69::Filter::Address(0x02).accepts(&header));
+    assert!(!rh_rf69::Filter::Address(0x03).accepts(&header));
+    assert!(rh_rf69::Filter::Address(0x03).accepts(&rh_rf69::Header {
+        to: rh_rf69::BROADCAST_ADDRESS,
+        ..header
+    }));
+    assert!(rh_rf69::Filter::Promiscuous.accepts(&header));
+}
+
+#[test]
+fn test_send_rh() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0, 0b0000_1_000]);
+    let header = rh_rf69::Header {
+        to: 0xff,
+        from: 0x01,
+        id: 0x05,
+        flags: 0x00,
+    };
+
+    rfm.send_rh(header, &[0x12]).ok().unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[4..=10],
+        [Registers::Fifo.write(), 5, 0xff, 0x01, 0x05, 0x00, 0x12]
+    );
+
+    assert!(matches!(
+        rfm.send_rh(header, &[0; 61]).err().unwrap(),
+        Error::Frame(FrameError::PayloadTooLarge)
+    ));
+}
+
+#[test]
+fn test_recv_rh() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0b1000_0_1_00, 0b00000_1_00]);
+    let header = |to, id| rh_rf69::Header {
+        to,
+        from: 0x01,
+        id,
+        flags: 0x00,
+    };
+    let mut buffer = [0u8; 65];
+
+    rfm.spi.fifo.push_back(vec![2, 0x02, 0x01]);
+    rfm.spi
+        .fifo
+        .push_back(vec![5, 0x03, 0x01, 0x07, 0x00, b'a']);
+    rfm.spi
+        .fifo
+        .push_back(vec![5, 0x02, 0x01, 0x08, 0x00, b'b']);
+    let frame = rfm
+        .recv_rh(rh_rf69::Filter::Address(0x02), &mut buffer)
+        .ok()
+        .unwrap();
+    assert_eq!(frame.header, header(0x02, 0x08));
+    assert_eq!(frame.payload, b"b");
+    assert!(rfm.spi.fifo.is_empty());
+
+    rfm.spi
+        .fifo
+        .push_back(vec![5, 0xff, 0x01, 0x09, 0x00, b'c']);
+    let frame = rfm
+        .recv_rh(rh_rf69::Filter::Address(0x02), &mut buffer)
+        .ok()
+        .unwrap();
+    assert_eq!(frame.header, header(rh_rf69::BROADCAST_ADDRESS, 0x09));
+
+    rfm.spi
+        .fifo
+        .push_back(vec![5, 0x03, 0x01, 0x0a, 0x00, b'd']);
+    let frame = rfm
+        .recv_rh(rh_rf69::Filter::Promiscuous, &mut buffer)
+        .ok()
+        .unwrap();
+    assert_eq!(frame.header, header(0x03, 0x0a));
+
+    rfm.spi.rx_buffer.clear();
+    assert!(matches!(
+        rfm.recv_rh(rh_rf69::Filter::Promiscuous, &mut buffer[..4])
+            .err()
+            .unwrap(),
+        Error::BufferTooSmall
+    ));
+    assert!(rfm.spi.rx_buffer.is_empty());
+}
+
+#[test]
+fn test_recv_rh_timeout() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0b1000_0_1_00, 0b00000_1_00]);
+    let mut buffer = [0u8; 65];
+
+    rfm.spi
+        .fifo
+        .push_back(vec![5, 0x03, 0x01, 0x07, 0x00, b'a']);
+    rfm.spi
+        .fifo
+        .push_back(vec![5, 0x02, 0x01, 0x08, 0x00, b'b']);
+    let frame = rfm
+        .recv_rh_timeout(rh_rf69::Filter::Address(0x02), &mut buffer, 100)
+        .ok()
+        .unwrap();
+    assert_eq!(frame.header.id, 0x08);
+    assert_eq!(frame.payload, b"b");
+
+    rfm.spi.tx_buffer = vec![0b1000_0_0_00, 0b00000_0_00];
+    assert!(matches!(
+        rfm.recv_rh_timeout(rh_rf69::Filter::Address(0x02), &mut buffer, 100)
+            .err()
+            .unwrap(),
+        Error::RxTimeout
+    ));
+}
+
+#[test]
+fn test_reliable_datagram() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0b1_00000_1_0, 0b0000_1_000]);
+    let mut datagram = rh_reliable_datagram::ReliableDatagram::new(0x01);
+    let retry = rh_reliable_datagram::FLAGS_RETRY;
+
+    assert!(matches!(
+        datagram.sendto_wait(&mut rfm, b"x", 0x02).err().unwrap(),
+        Error::NoAck
+    ));
+    assert_eq!(
+        rfm.spi.sent,
+        [
+            [5, 0x02, 0x01, 1, 0x00, b'x'],
+            [5, 0x02, 0x01, 1, retry, b'x'],
+            [5, 0x02, 0x01, 1, retry, b'x'],
+            [5, 0x02, 0x01, 1, retry, b'x']
+        ]
+    );
+    assert_eq!(datagram.retransmissions(), 3);
+
+    rfm.spi.sent.clear();
+    datagram.retries(0);
+    datagram.timeout(10);
+    datagram.reset_retransmissions();
+    datagram.sendto_wait(&mut rfm, b"x", 0x02).err().unwrap();
+    assert_eq!(rfm.spi.sent, [[5, 0x02, 0x01, 2, 0x00, b'x']]);
+    assert_eq!(datagram.retransmissions(), 0);
+
+    rfm.spi.sent.clear();
+    for _ in 0..2 {
+        datagram
+            .sendto_wait(&mut rfm, b"x", rh_rf69::BROADCAST_ADDRESS)
+            .ok()
+            .unwrap();
+    }
+    assert_eq!(
+        rfm.spi.sent,
+        [
+            [5, 0xff, 0x01, 3, 0x00, b'x'],
+            [5, 0xff, 0x01, 4, 0x00, b'x']
+        ]
+    );
+
+    let mut datagram = rh_reliable_datagram::ReliableDatagram::new(0x01);
+    datagram.retries(0);
+    datagram.timeout(u32::MAX);
+    rfm.spi.tx_buffer[1] = 0b0000_1_1_00;
+    rfm.spi.fifo.push_back(vec![
+        5,
+        0x01,
+        0x02,
+        1,
+        rh_reliable_datagram::FLAGS_ACK,
+        b'!',
+    ]);
+    datagram.sendto_wait(&mut rfm, b"x", 0x02).ok().unwrap();
+}
+
+#[test]
+fn test_reliable_datagram_recv() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0b1000_0_1_00, 0b0000_1_1_00]);
+    let mut datagram = rh_reliable_datagram::ReliableDatagram::new(0x02);
+    let flags = rh_reliable_datagram::FLAGS_ACK;
+    let ack = |to, id| vec![5, to, 0x02, id, flags, b'!'];
+    let mut buffer = [0u8; 65];
+
+    rfm.spi
+        .fifo
+        .push_back(vec![5, 0x02, 0x01, 0x07, 0x00, b'a']);
+    let frame = datagram.recvfrom_ack(&mut rfm, &mut buffer).ok().unwrap();
+    assert_eq!(frame.header.from, 0x01);
+    assert_eq!(frame.payload, b"a");
+    assert_eq!(rfm.spi.sent, [ack(0x01, 0x07)]);
+
+    // The duplicate is acknowledged again, the ACK is ignored and ids are tracked per node.
+    rfm.spi.sent.clear();
+    rfm.spi
+        .fifo
+        .push_back(vec![5, 0x02, 0x01, 0x07, 0x00, b'a']);
+    rfm.spi
+        .fifo
+        .push_back(vec![5, 0x02, 0x03, 0x07, flags, b'!']);
+    rfm.spi
+        .fifo
+        .push_back(vec![5, 0x02, 0x03, 0x07, 0x00, b'b']);
+    let frame = datagram.recvfrom_ack(&mut rfm, &mut buffer).ok().unwrap();
+    assert_eq!(frame.header.from, 0x03);
+    assert_eq!(frame.payload, b"b");
+    assert_eq!(rfm.spi.sent, [ack(0x01, 0x07), ack(0x03, 0x07)]);
+
+    rfm.spi.sent.clear();
+    rfm.spi
+        .fifo
+        .push_back(vec![5, 0xff, 0x01, 0x08, 0x00, b'c']);
+    let frame = datagram.recvfrom_ack(&mut rfm, &mut buffer).ok().unwrap();
+    assert_eq!(frame.header.to, rh_rf69::BROADCAST_ADDRESS);
+    assert!(rfm.spi.sent.is_empty());
+
+    rfm.spi
+        .fifo
+        .push_back(vec![5, 0x02, 0x01, 0x08, 0x00, b'c']);
+    rfm.spi
+        .fifo
+        .push_back(vec![5, 0x02, 0x01, 0x09, 0x00, b'd']);
+    let frame = datagram
+        .recvfrom_ack_timeout(&mut rfm, &mut buffer, 100)
+        .ok()
+        .unwrap();
+    assert_eq!(frame.header.id, 0x09);
+    assert_eq!(rfm.spi.sent, [ack(0x01, 0x08), ack(0x01, 0x09)]);
+
+    rfm.spi.tx_buffer = vec![0b1000_0_0_00, 0b0000_0_0_00];
+    assert!(matches!(
+        datagram
+            .recvfrom_ack_timeout(&mut rfm, &mut buffer, 100)
+            .err()
+            .unwrap(),
+        Error::RxTimeout
+    ));
+}
+
+#[test]
+fn test_lpl_frame() {
+    let frame = lowpowerlab::Frame {
+        to: 1,
+        from: 10,
+        control: lowpowerlab::ACK_REQUESTED,
+        payload: b"Hi",
+    };
+
+    let mut buffer = [0u8; 8];
+    assert_eq!(frame.encode(&mut buffer), Ok(6));
+    assert_eq!(buffer[..6], [5, 1, 10, 0x40, b'H', b'i']);
+    let decoded = lowpowerlab::Frame::decode(&buffer).ok().unwrap();
+    assert_eq!(decoded, frame);
+    assert!(decoded.ack_requested());
+    assert!(!decoded.ack_received());
+
+    let ack = decoded.ack(1);
+    assert_eq!(
+        ack,
+        lowpowerlab::Frame {
+            to: 10,
+            from: 1,
+            control: lowpowerlab::ACK_SENT,
+            payload: &[],
+        }
+    );
+    assert!(ack.ack_received());
+
+    assert_eq!(
+        frame.encode(&mut buffer[..5]),
+        Err(FrameError::BufferTooSmall)
+    );
+    assert_eq!(
+        lowpowerlab::Frame::decode(&buffer[..5]),
+        Err(FrameError::Truncated)
+    );
+    assert_eq!(
+        lowpowerlab::Frame::decode(&[2, 0, 0]),
+        Err(FrameError::InvalidLength)
+    );
+    assert_eq!(
+        lowpowerlab::Frame::decode(&[65, 0, 0, 0]),
+        Err(FrameError::PayloadTooLarge)
+    );
+}
+
+#[test]
+fn test_lpl_address() {
+    let frame = lowpowerlab::Frame {
+        to: 0x2f1,
+        from: 0x3ff,
+        control: lowpowerlab::ACK_REQUESTED | lowpowerlab::RSSI_REQUESTED,
+        payload: &[],
+    };
+
+    let mut buffer = [0u8; 4];
+    assert_eq!(frame.encode(&mut buffer), Ok(4));
+    assert_eq!(buffer, [3, 0xf1, 0xff, 0x6b]);
+    let decoded = lowpowerlab::Frame::decode(&buffer).ok().unwrap();
+    assert_eq!(decoded, frame);
+    assert!(decoded.rssi_requested());
+    assert_eq!(decoded.ack(0x2f1).to, 0x3ff);
+    let broadcast = lowpowerlab::Frame {
+        to: lowpowerlab::BROADCAST_ADDRESS,
+        ..frame
+    };
+    assert_eq!(broadcast.ack(0x2f1).from, 0x2f1);
+
+    let filter = lowpowerlab::Filter::Address(0x2f1);
+    assert!(filter.accepts(&frame));
+    assert!(!filter.accepts(&lowpowerlab::Frame { to: 0xf1, ..frame }));
+    assert!(filter.accepts(&lowpowerlab::Frame {
+        to: lowpowerlab::BROADCAST_ADDRESS,
+        ..frame
+    }));
+    assert!(lowpowerlab::Filter::Promiscuous.accepts(&lowpowerlab::Frame { to: 0xf1, ..frame }));
+
+    assert_eq!(
+        lowpowerlab::Frame { to: 0x400, ..frame }.encode(&mut buffer),
+        Err(FrameError::InvalidAddress)
+    );
+}
+
+#[test]
+fn test_send_lpl() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0, 0b0000_1_000]);
+
+    rfm.send_lpl(&lowpowerlab::Frame {
+        to: 1,
+        from: 10,
+        control: 0,
+        payload: &[0x12],
+    })
+    .ok()
+    .unwrap();
+    assert_eq!(
+        rfm.spi.rx_buffer[4..=9],
+        [Registers::Fifo.write(), 4, 1, 10, 0, 0x12]
+    );
+}
+
+#[test]
+fn test_recv_lpl() {
+    let mut rfm = setup_rfm(Vec::new(), vec![0b1000_0_1_00, 0]);
+    let filter = lowpowerlab::Filter::Address(0x1f1);
+    let mut buffer = [0u8; 65];
+
+    rfm.spi.fifo.push_back(vec![2, 0xf1, 10]);
+    rfm.spi
+        .fifo
+        .push_back(vec![4, 0xf1, 10, 0b0000_10_00, b'a']);
+    rfm.spi
+        .fifo
+        .push_back(vec![4, 0xf1, 10, 0b0000_01_00, b'b']);
+    let frame = rfm.recv_lpl(filter, &mut buffer).ok().unwrap();
+    assert_eq!(frame.to, 0x1f1);
+    assert_eq!(frame.from, 10);
+    assert_eq!(frame.payload, b"b");
+    assert!(rfm.spi.fifo.is_empty());
+
+    rfm.spi
+        .fifo
+        .push_back(vec![4, 0x00, 0x02, 0b0100_00_11, b'c']);
+    let frame = rfm.recv_lpl(filter, &mut buffer).ok().unwrap();
+    assert_eq!(frame.to, lowpowerlab::BROADCAST_ADDRESS);
+    assert_eq!(frame.from, 0x302);
+    assert!(frame.ack_requested());
+
+    rfm.spi
+        .fifo
+        .push_back(vec![4, 0xf1, 10, 0b0000_10_00, b'd']);
+    let frame = rfm
+        .recv_lpl(lowpowerlab::Filter::Promiscuous, &mut buffer)
+        .ok()
+        .unwrap();
+    assert_eq!(frame.to, 0x2f1);
+    assert_eq!(frame.payload, b"d");
+
+    assert!(matches!(
+        rfm.recv_lpl(filter, &mut buffer[..3]).err().unwrap(),
+        Error::BufferTooSmall
+    ));
+}